@@ -0,0 +1,33 @@
+//! Bakes the examples of every day into the binary, for `--examples`. See
+//! `src/runner/examples.rs`.
+
+use std::{env, fmt::Write as _, fs, path::Path};
+
+#[path = "src/runner/examples/scrape.rs"]
+mod scrape;
+#[path = "src/runner/examples/sources.rs"]
+mod sources;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/solutions");
+    println!("cargo:rerun-if-changed=src/runner/examples/scrape.rs");
+    println!("cargo:rerun-if-changed=src/runner/examples/sources.rs");
+
+    let solutions = Path::new("src/solutions");
+
+    let mut table = String::from("&[\n");
+    for day in 1..=25 {
+        // Days that haven't been started yet don't have any source.
+        let Ok(sources) = sources::source_files(solutions, day) else {
+            continue;
+        };
+
+        for (part, input, expected) in scrape::examples(&sources) {
+            writeln!(table, "    ({day}, {part}, {input:?}, {expected:?}),").unwrap();
+        }
+    }
+    table.push(']');
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("examples.rs"), table).unwrap();
+}
@@ -0,0 +1,68 @@
+//! What fallible solutions return.
+//!
+//! `elvish` only takes answers it can `Display`, and hands them to the runner
+//! as strings. So errors are displayed with [`ERROR_PREFIX`] in front, and
+//! [`decode`] tells them apart from answers again.
+
+use std::fmt;
+
+use color_eyre::eyre;
+
+/// Can't be typed as part of an answer, so nothing else starts with it.
+const ERROR_PREFIX: &str = "\0error: ";
+
+/// An `eyre::Result` that `elvish` can display.
+#[derive(Debug)]
+pub struct Answer<T>(eyre::Result<T>);
+
+impl<T> Answer<T> {
+    /// Runs `solve`, so that solutions can use `?` in their body.
+    pub fn new(solve: impl FnOnce() -> eyre::Result<T>) -> Self {
+        Self(solve())
+    }
+
+    /// For tests, which check the answer itself.
+    #[cfg(test)]
+    pub fn into_result(self) -> eyre::Result<T> {
+        self.0
+    }
+}
+
+impl<T> From<eyre::Result<T>> for Answer<T> {
+    fn from(result: eyre::Result<T>) -> Self {
+        Self(result)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Answer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Ok(answer) => answer.fmt(f),
+            Err(error) => write!(f, "{ERROR_PREFIX}{error:#}"),
+        }
+    }
+}
+
+/// Turns what `elvish` gives back for an [`Answer`] into the answer or the
+/// error message.
+pub fn decode(output: String) -> Result<String, String> {
+    match output.strip_prefix(ERROR_PREFIX) {
+        Some(error) => Err(error.to_string()),
+        None => Ok(output),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let answer = Answer::from(Ok(42));
+    assert_eq!(decode(answer.to_string()), Ok("42".to_string()));
+
+    let error = Answer::<u64>::new(|| {
+        let number = "nope".parse::<u64>()?;
+        Ok(number)
+    });
+    assert_eq!(
+        decode(error.to_string()),
+        Err("invalid digit found in string".to_string())
+    );
+}
@@ -0,0 +1,239 @@
+//! A 2D grid of tiles, like most days' inputs.
+//!
+//! Positions are `(x, y)`, with `x` going right and `y` going down from the
+//! top left corner. The underlying `Array2` is indexed the other way around, as
+//! `(row, column)`.
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use color_eyre::eyre;
+use ndarray::Array2;
+use pathfinding::matrix::directions::DIRECTIONS_8;
+
+use crate::{
+    parse::{self, ParseError},
+    vec2::{Direction, Vec2, Vec2Ext as _},
+};
+
+pub type Position = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    tiles: Array2<T>,
+}
+
+impl<T> Grid<T> {
+    /// Parses a grid with a line per row, turning each character into a tile
+    /// with `tile`. Characters it returns `None` for are errors.
+    pub fn parse_with(
+        input: &str,
+        mut tile: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        Self::parse_wide(input, |char| tile(char).map(|tile| [tile]))
+    }
+
+    /// Like [`Grid::parse_with`], but each character becomes `N` tiles side by side.
+    pub fn parse_wide<const N: usize>(
+        input: &str,
+        tiles: impl FnMut(char) -> Option<[T; N]>,
+    ) -> Result<Self, ParseError> {
+        let blank = input
+            .split_inclusive('\n')
+            .take_while(|line| line.trim().is_empty())
+            .map(str::len)
+            .sum::<usize>();
+
+        parse::prefix(input, &mut &input[blank..], parse::grid_wide(tiles))
+    }
+
+    pub fn from_array(tiles: Array2<T>) -> Self {
+        Self { tiles }
+    }
+
+    pub fn as_array(&self) -> &Array2<T> {
+        &self.tiles
+    }
+
+    pub fn into_array(self) -> Array2<T> {
+        self.tiles
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles.ncols()
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.nrows()
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        x < self.width() && y < self.height()
+    }
+
+    pub fn get(&self, (x, y): Position) -> Option<&T> {
+        self.tiles.get((y, x))
+    }
+
+    pub fn get_mut(&mut self, (x, y): Position) -> Option<&mut T> {
+        self.tiles.get_mut((y, x))
+    }
+
+    /// Like [`Grid::get`], but with a `Vec2` which might be outside of the grid.
+    pub fn at(&self, position: Vec2) -> Option<&T> {
+        self.get(position.to_position()?)
+    }
+
+    /// `position` moved by `(dx, dy)`, if it's still inside the grid.
+    pub fn offset(&self, (x, y): Position, (dx, dy): (isize, isize)) -> Option<Position> {
+        let position = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(position).then_some(position)
+    }
+
+    /// `position` moved one tile towards `direction`, if it's still inside the grid.
+    pub fn step(&self, position: Position, direction: Direction) -> Option<Position> {
+        self.offset(position, direction.delta())
+    }
+
+    /// The (up to) 4 orthogonal neighbours of `position` inside the grid.
+    pub fn neighbors4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(position, direction))
+    }
+
+    /// The (up to) 8 neighbours of `position` inside the grid, diagonals included.
+    pub fn neighbors8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        DIRECTIONS_8
+            .into_iter()
+            .filter_map(move |delta| self.offset(position, delta))
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> + use<T> {
+        let width = self.width();
+        (0..self.height()).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every tile with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.tiles.indexed_iter().map(|((y, x), tile)| ((x, y), tile))
+    }
+
+    pub fn swap(&mut self, (ax, ay): Position, (bx, by): Position) {
+        self.tiles.swap((ay, ax), (by, bx));
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            tiles: self.tiles.map(f),
+        }
+    }
+}
+
+impl<T: TryFrom<char>> Grid<T> {
+    /// Parses a grid where every character converts to a tile.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, |char| T::try_from(char).ok())
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Every position that has `tile`.
+    pub fn find_all<'a>(&'a self, tile: &'a T) -> impl Iterator<Item = Position> + 'a {
+        self.iter()
+            .filter(move |(_, other)| *other == tile)
+            .map(|(position, _)| position)
+    }
+
+    /// The position of a marker (such as the start or the end), which has to
+    /// be in the grid exactly once.
+    pub fn find(&self, marker: &T) -> eyre::Result<Position>
+    where
+        T: fmt::Display,
+    {
+        let mut positions = self.find_all(marker);
+        match (positions.next(), positions.count()) {
+            (Some(position), 0) => Ok(position),
+            (None, _) => eyre::bail!("There's no `{marker}` in the grid"),
+            (Some(_), others) => {
+                eyre::bail!("Expected one `{marker}` in the grid, found {}", others + 1)
+            }
+        }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+    fn index(&self, (x, y): Position) -> &Self::Output {
+        &self.tiles[(y, x)]
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, (x, y): Position) -> &mut Self::Output {
+        &mut self.tiles[(y, x)]
+    }
+}
+
+impl<T> From<Array2<T>> for Grid<T> {
+    fn from(tiles: Array2<T>) -> Self {
+        Self::from_array(tiles)
+    }
+}
+
+impl<T> From<Grid<T>> for Array2<T> {
+    fn from(grid: Grid<T>) -> Self {
+        grid.into_array()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.tiles.rows() {
+            for tile in row {
+                write!(f, "{tile}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn parse_grid() {
+    let input = "#S.\n.#E\n";
+    let grid = Grid::<char>::parse(input).unwrap();
+
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[(2, 1)], 'E');
+    assert_eq!(grid.find(&'S').unwrap(), (1, 0));
+    assert!(grid.find(&'x').is_err());
+    assert_eq!(grid.to_string(), input);
+
+    let mut neighbors = grid.neighbors4((0, 0)).collect::<Vec<_>>();
+    neighbors.sort();
+    assert_eq!(neighbors, [(0, 1), (1, 0)]);
+    assert_eq!(grid.neighbors8((1, 0)).count(), 5);
+    assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+    assert_eq!(grid.step((0, 0), Direction::Right), Some((1, 0)));
+    assert_eq!(grid.at(Vec2::new(2, 1)), Some(&'E'));
+    assert_eq!(grid.at(Vec2::new(-1, 1)), None);
+
+    let wide = Grid::parse_wide(input, |char| Some([char, '-'])).unwrap();
+    assert_eq!(wide.to_string(), "#-S-.-\n.-#-E-\n");
+
+    let error = Grid::<char>::parse("...\n..\n").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 1));
+
+    let error = Grid::parse_with("..\n.x\n", |char| (char == '.').then_some(())).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 2));
+
+    let array = Array2::from(grid.clone());
+    assert_eq!(array[(1, 2)], 'E');
+    assert_eq!(Grid::from(array), grid);
+}
@@ -1,17 +1,40 @@
+use clap::Parser as _;
 use color_eyre::eyre;
 
 mod solutions;
+mod answer;
 mod parse;
+mod grid;
+mod vec2;
+mod runner;
 
 pub struct Solutions;
 
 elvish::declare::run_fn!();
 
 fn main() -> eyre::Result<()> {
-    tracing_subscriber::fmt().init();
+    tracing_subscriber::fmt().with_writer(std::io::stderr).init();
     dotenvy::dotenv()?;
 
-    elvish::run::<2024>(&elvish::available_days!(), run_day_part)?;
+    let args = runner::Args::parse();
+    let days = elvish::available_days!();
 
-    Ok(())
+    #[cfg(feature = "today")]
+    let days = runner::today::select::<2024>(&days, runner::today::Date::puzzle_today())?;
+
+    let inputs = runner::inputs::InputStore::from_args(&args);
+
+    if cfg!(feature = "generate-docs") {
+        return runner::docs::generate(&days, run_day_part, &inputs);
+    }
+
+    if let Some(dir) = &args.inspect {
+        return runner::inspect::run(&days, &inputs, dir);
+    }
+
+    if args.bench {
+        return runner::bench::run(&days, run_day_part, &inputs, &args);
+    }
+
+    runner::run(&days, run_day_part, &inputs, &args)
 }
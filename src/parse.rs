@@ -1,9 +1,330 @@
-use std::str::FromStr;
+//! Parsers shared by the days, built on `winnow`.
+//!
+//! Each day's `parse` usually runs a parser made out of these with [`all`],
+//! which turns failures into a [`ParseError`] pointing at the line and column.
 
-use nom::{character::complete::digit1, Parser as _};
-use nom_supreme::ParserExt as _;
+use std::{fmt, str::FromStr};
 
-pub fn digit<T>(input: &str) -> nom::IResult<&str, T> where T: FromStr {
-    digit1.map_res(str::parse).parse(input)
+use winnow::{
+    ascii::{digit1, line_ending, space0, space1, till_line_ending},
+    combinator::{cut_err, fail, opt, preceded, repeat, separated, separated_pair},
+    error::{ContextError, StrContext, StrContextValue},
+    stream::Stream as _,
+    token::one_of,
+    PResult, Parser,
+};
+
+use crate::grid::Grid;
+
+/// An error in the input, pointing at where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    /// 1-based, like editors show them.
+    pub line: usize,
+    pub column: usize,
+    /// The whole line the error is in.
+    pub source_line: String,
+}
+
+impl ParseError {
+    /// An error at byte `offset` of `source`.
+    pub fn at_offset(source: &str, offset: usize, message: impl fmt::Display) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        ParseError {
+            message: message.to_string(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            source_line: source[line_start..].lines().next().unwrap_or("").to_string(),
+        }
+    }
+
+    /// An error at the start of `position`, which has to be a slice of
+    /// `source` (such as what's left of it after parsing some).
+    pub fn at(source: &str, position: &str, message: impl fmt::Display) -> Self {
+        Self::at_offset(source, offset_in(source, position), message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} at line {}, column {}:", self.message, self.line, self.column)?;
+        writeln!(f, "    {}", self.source_line)?;
+        write!(f, "    {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Where `slice` starts in `source`, or the end of `source` if it isn't part of it.
+fn offset_in(source: &str, slice: &str) -> usize {
+    let start = source.as_ptr() as usize;
+    let position = slice.as_ptr() as usize;
+
+    if (start..=start + source.len()).contains(&position) {
+        position - start
+    } else {
+        source.len()
+    }
+}
+
+impl ParseError {
+    /// From a winnow error at byte `offset` of `source`. Its `StrContext`s
+    /// become the message.
+    fn from_winnow(source: &str, offset: usize, error: &ContextError) -> Self {
+        let message = match error.to_string() {
+            context if context.is_empty() => "Couldn't parse".to_string(),
+            context => context.replace('\n', ", "),
+        };
+
+        Self::at_offset(source, offset, message)
+    }
+}
+
+/// Runs `parser` over all of `input` but the surrounding whitespace. This is
+/// how most days parse their input.
+pub fn all<'a, O>(
+    input: &'a str,
+    parser: impl Parser<&'a str, O, ContextError>,
+) -> Result<O, ParseError> {
+    winnow(input, input.trim_ascii(), parser)
+}
+
+/// Runs `parser` over all of `input`, which is a slice of `source`, so errors
+/// point to the right place.
+pub fn winnow<'a, O>(
+    source: &'a str,
+    input: &'a str,
+    mut parser: impl Parser<&'a str, O, ContextError>,
+) -> Result<O, ParseError> {
+    parser.parse(input).map_err(|error| {
+        ParseError::from_winnow(
+            source,
+            offset_in(source, input) + error.offset(),
+            error.inner(),
+        )
+    })
+}
+
+/// Runs `parser` over the start of `input` (a slice of `source`), leaving
+/// what it didn't parse in `input`.
+pub fn prefix<'a, O>(
+    source: &'a str,
+    input: &mut &'a str,
+    mut parser: impl Parser<&'a str, O, ContextError>,
+) -> Result<O, ParseError> {
+    parser.parse_next(input).map_err(|error| {
+        let error = error.into_inner().expect("`&str` is never partial");
+        ParseError::from_winnow(source, offset_in(source, input), &error)
+    })
+}
+
+/// An unsigned integer, like `42`.
+pub fn uint<T: FromStr>(input: &mut &str) -> PResult<T> {
+    digit1
+        .parse_to()
+        .context(StrContext::Label("number"))
+        .parse_next(input)
+}
+
+/// An integer with an optional sign, like `-42` or `+7`.
+pub fn int<T: FromStr>(input: &mut &str) -> PResult<T> {
+    (opt(one_of(['+', '-'])), digit1)
+        .take()
+        .parse_to()
+        .context(StrContext::Label("number"))
+        .parse_next(input)
+}
+
+/// One or more `item`s separated by commas, like `1,2, 3`.
+pub fn comma_list<'a, O>(
+    item: impl Parser<&'a str, O, ContextError>,
+) -> impl Parser<&'a str, Vec<O>, ContextError> {
+    separated(1.., item, (',', space0))
+}
+
+/// One or more `item`s separated by spaces, like `1 2  3`.
+pub fn space_list<'a, O>(
+    item: impl Parser<&'a str, O, ContextError>,
+) -> impl Parser<&'a str, Vec<O>, ContextError> {
+    separated(1.., item, space1)
+}
+
+/// An `item` per line, up to a blank line or the end.
+pub fn lines<'a, O>(
+    item: impl Parser<&'a str, O, ContextError>,
+) -> impl Parser<&'a str, Vec<O>, ContextError> {
+    separated(1.., item, line_ending)
 }
 
+/// The end of a line followed by one or more blank lines, which is what
+/// separates the sections of an input.
+pub fn blank_line(input: &mut &str) -> PResult<()> {
+    (line_ending, repeat::<_, _, (), _, _>(1.., (space0, line_ending)))
+        .void()
+        .parse_next(input)
+}
+
+/// One or more `item`s separated by blank lines.
+pub fn blocks<'a, O>(
+    item: impl Parser<&'a str, O, ContextError>,
+) -> impl Parser<&'a str, Vec<O>, ContextError> {
+    separated(1.., item, blank_line)
+}
+
+/// `key: value`, like `190: 10 19`.
+pub fn key_value<'a, K, V>(
+    key: impl Parser<&'a str, K, ContextError>,
+    value: impl Parser<&'a str, V, ContextError>,
+) -> impl Parser<&'a str, (K, V), ContextError> {
+    separated_pair(key, (':', space0), value)
+}
+
+/// The value of a `key: value` record with a known key, like `Register A: 729`.
+pub fn field<'a, O>(
+    key: &'static str,
+    value: impl Parser<&'a str, O, ContextError>,
+) -> impl Parser<&'a str, O, ContextError> {
+    preceded((key, ':', space0), value).context(StrContext::Label(key))
+}
+
+/// A block of lines (up to a blank line or the end) as a [`Grid`], turning
+/// each character into a tile with `tile`. Characters it returns `None` for
+/// are errors.
+pub fn grid<'a, T>(
+    mut tile: impl FnMut(char) -> Option<T>,
+) -> impl Parser<&'a str, Grid<T>, ContextError> {
+    grid_wide(move |char| tile(char).map(|tile| [tile]))
+}
+
+/// Like [`grid`], but each character becomes `N` tiles side by side.
+pub fn grid_wide<'a, T, const N: usize>(
+    mut tiles: impl FnMut(char) -> Option<[T; N]>,
+) -> impl Parser<&'a str, Grid<T>, ContextError> {
+    move |input: &mut &'a str| {
+        let mut data = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        loop {
+            let before = input.checkpoint();
+            if height > 0 && opt(line_ending).parse_next(input)?.is_none() {
+                break;
+            }
+
+            let start = input.checkpoint();
+            let line = till_line_ending.parse_next(input)?;
+            if line.trim().is_empty() {
+                input.reset(&before);
+                break;
+            }
+
+            for (i, char) in line.char_indices() {
+                let Some(tiles) = tiles(char) else {
+                    input.reset(&start);
+                    input.next_slice(i);
+                    return cut_err(fail.context(StrContext::Label("tile"))).parse_next(input);
+                };
+
+                data.extend(tiles);
+            }
+
+            let row_width = line.chars().count();
+            if *width.get_or_insert(row_width) != row_width {
+                input.reset(&start);
+                return cut_err(fail.context(StrContext::Label("row")).context(
+                    StrContext::Expected(StrContextValue::Description(
+                        "as many tiles as the first row",
+                    )),
+                ))
+                .parse_next(input);
+            }
+
+            height += 1;
+        }
+
+        let Some(width) = width else {
+            return fail.context(StrContext::Label("grid")).parse_next(input);
+        };
+
+        let tiles = ndarray::Array2::from_shape_vec((height, width * N), data)
+            .expect("Every row has the same width");
+
+        Ok(Grid::from_array(tiles))
+    }
+}
+
+#[test]
+fn error_location() {
+    let source = "1 2\n3 x\n";
+    let line = source.lines().nth(1).unwrap();
+    let error = winnow(
+        source,
+        line,
+        separated_pair(uint::<i32>, space1, uint::<i32>),
+    )
+    .unwrap_err();
+
+    assert_eq!((error.line, error.column), (2, 3));
+    assert_eq!(
+        error.to_string(),
+        "invalid number at line 2, column 3:\n    3 x\n      ^"
+    );
+
+    let error = winnow(source, &source[6..7], uint::<u8>).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 3));
+
+    let error = winnow(source, source, ("1 2\n", "4")).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 1));
+}
+
+#[test]
+fn combinators() {
+    let input = "Register A: -12\n\n1,2, 3\n4 5\n\n\n#.\n.#\n";
+    let (register, (lists, tiles)) = all(
+        input,
+        separated_pair(
+            field("Register A", int::<i64>),
+            blank_line,
+            separated_pair(
+                (comma_list(uint::<u8>), line_ending, space_list(uint::<u8>)),
+                blank_line,
+                grid(|char| Some(char == '#')),
+            ),
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(register, -12);
+    assert_eq!((lists.0, lists.2), (vec![1, 2, 3], vec![4, 5]));
+    assert_eq!(tiles.to_string(), "truefalse\nfalsetrue\n");
+
+    let records = all(
+        "a: 1 2\nb: 3",
+        lines(key_value(one_of('a'..='z'), space_list(uint::<u32>))),
+    )
+    .unwrap();
+    assert_eq!(records, [('a', vec![1, 2]), ('b', vec![3])]);
+
+    let blocks = all("1\n2\n\n3", blocks(lines(uint::<u32>))).unwrap();
+    assert_eq!(blocks, [vec![1, 2], vec![3]]);
+
+    let dots = grid(|char| (char == '.').then_some(()));
+    let error = all(
+        "1 2\n\n..\n.x",
+        separated_pair(space_list(uint::<u32>), blank_line, dots),
+    )
+    .unwrap_err();
+    assert_eq!((error.line, error.column), (4, 2));
+    assert!(error.message.starts_with("invalid tile"));
+
+    let error = all("Register A: x", field("Register A", int::<i64>)).unwrap_err();
+    assert_eq!((error.line, error.column), (1, 13));
+}
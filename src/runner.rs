@@ -0,0 +1,181 @@
+// Things the `elvish` runner doesn't do on its own, toggled through the same
+// cargo features it detects.
+
+pub mod bench;
+pub mod docs;
+pub mod examples;
+pub mod inputs;
+pub mod inspect;
+pub mod ledger;
+pub mod output;
+#[cfg(any(feature = "today", test))]
+pub mod today;
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre;
+
+use crate::answer;
+use inputs::InputStore;
+use ledger::Ledger;
+use output::{ExampleCheck, Format, Printer, Record};
+
+#[derive(Debug, clap::Parser)]
+pub struct Args {
+    /// Time every selected part instead of running it once.
+    #[arg(long)]
+    pub bench: bool,
+
+    /// How many times each part runs when benchmarking.
+    #[arg(long, default_value_t = 10)]
+    pub iterations: usize,
+
+    /// Store the benchmark results as the new baseline.
+    #[arg(long)]
+    pub save_baseline: bool,
+
+    /// Record the answers of this run as accepted in the ledger.
+    #[arg(long)]
+    pub accept: bool,
+
+    /// Record the answers of this run as wrong in the ledger, with a note
+    /// (e.g. "Too high").
+    #[arg(long, value_name = "NOTE")]
+    pub reject: Option<String>,
+
+    /// Only use inputs that are already in `inputs/`, never download them.
+    #[arg(long)]
+    pub offline: bool,
+
+    /// How to print the results.
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
+
+    /// Also run the examples, and report whether they give the expected answer.
+    #[arg(long)]
+    pub examples: bool,
+
+    /// Write what helps debug each day (pictures, traces, graphs...) to this
+    /// directory instead of running it.
+    #[arg(long, value_name = "DIR")]
+    pub inspect: Option<PathBuf>,
+}
+
+/// The signature of `run_day_part`, as generated by `elvish::declare::run_fn!`.
+///
+/// Returns `None` if the part isn't implemented. Solutions that can fail return
+/// an [`Answer`](answer::Answer), which [`run_part`] decodes back into an error.
+pub type Solver = fn(u8, u8, &str) -> Option<String>;
+
+/// The outcome of running a single part of a day.
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// The answer, or why there isn't one (the error or panic message).
+    pub answer: Result<String, String>,
+    pub duration: Duration,
+}
+
+/// Parts enabled through the `part1`/`part2` features.
+pub fn parts() -> impl Iterator<Item = u8> {
+    [(1, cfg!(feature = "part1")), (2, cfg!(feature = "part2"))]
+        .into_iter()
+        .filter_map(|(part, enabled)| enabled.then_some(part))
+}
+
+/// Runs `day`'s `part` on `input`, catching errors and panics so that one
+/// broken day doesn't take the rest of the run down with it.
+pub fn run_part(solve: Solver, day: u8, part: u8, input: &str) -> Option<Run> {
+    let start = Instant::now();
+    let answer = panic::catch_unwind(AssertUnwindSafe(|| solve(day, part, input)));
+    let duration = start.elapsed();
+
+    let answer = match answer {
+        Ok(answer) => answer::decode(answer?),
+        Err(payload) => Err(format!("panicked: {}", panic_message(payload))),
+    };
+
+    Some(Run { answer, duration })
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Solution panicked".to_string()
+    }
+}
+
+pub fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Runs every enabled part of `days` on their real input, checking the answers
+/// against the ledger.
+pub fn run(days: &[u8], solve: Solver, inputs: &InputStore, args: &Args) -> eyre::Result<()> {
+    let mut ledger = Ledger::load()?;
+    let mut printer = Printer::new(args.format);
+
+    for &day in days {
+        let input = match inputs.get(day) {
+            Ok(input) => input,
+            Err(error) => {
+                for part in parts() {
+                    printer.print(Record::error(day, part, &error))?;
+                }
+
+                continue;
+            }
+        };
+
+        let input_hash = inputs::checksum(&input);
+        let examples = if args.examples {
+            examples::examples(day)
+        } else {
+            Vec::new()
+        };
+
+        for part in parts() {
+            let Some(run) = run_part(solve, day, part, &input) else {
+                continue;
+            };
+
+            let example_check = if args.examples {
+                ExampleCheck::run(&examples, solve, day, part)
+            } else {
+                ExampleCheck::Skipped
+            };
+
+            let Ok(answer) = &run.answer else {
+                printer.print(Record::new(day, part, &run, example_check, None))?;
+                continue;
+            };
+
+            let verdict = ledger.check(day, part, input_hash, answer);
+
+            if args.accept {
+                ledger.accept(day, part, input_hash, answer);
+            }
+
+            if let Some(note) = &args.reject {
+                ledger.reject(day, part, input_hash, answer, note);
+            }
+
+            printer.print(Record::new(day, part, &run, example_check, Some(verdict)))?;
+        }
+    }
+
+    printer.finish()?;
+
+    if args.accept || args.reject.is_some() {
+        ledger.save()?;
+    }
+
+    Ok(())
+}
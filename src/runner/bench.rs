@@ -0,0 +1,273 @@
+//! `--bench`: runs every selected part many times and compares the timings
+//! against a baseline stored in `bench-baseline.tsv`.
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    hint::black_box,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::{self, OptionExt as _};
+
+use super::{inputs::InputStore, manifest_dir, run_part, Args, Solver};
+
+/// A part is flagged as a regression if it's this much slower than the baseline...
+const REGRESSION_RATIO: f64 = 1.1;
+/// ...and the difference is bigger than this many standard deviations.
+const REGRESSION_DEVIATIONS: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Stage {
+    /// Only the day's `parse` function.
+    Parse,
+    /// The whole solution, including parsing.
+    Solve,
+    /// The whole solution, on a single rayon thread.
+    SingleThreaded,
+}
+
+impl Stage {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Parse => "parse",
+            Self::Solve => "solve",
+            Self::SingleThreaded => "solve (1 thread)",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Self::Parse, Self::Solve, Self::SingleThreaded]
+            .into_iter()
+            .find(|stage| stage.name() == name)
+    }
+}
+
+/// What a measurement is of. `part` is `None` for parsing, which is shared by both parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    day: u8,
+    part: Option<u8>,
+    stage: Stage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stats {
+    mean: Duration,
+    median: Duration,
+    stddev: Duration,
+}
+
+impl Stats {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "Need at least one sample");
+        samples.sort_unstable();
+
+        let n = samples.len() as f64;
+        let mean = samples.iter().map(Duration::as_secs_f64).sum::<f64>() / n;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        Stats {
+            mean: Duration::from_secs_f64(mean),
+            median: samples[samples.len() / 2],
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+
+    fn measure(iterations: usize, mut f: impl FnMut()) -> Self {
+        let samples = (0..iterations.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                f();
+                start.elapsed()
+            })
+            .collect();
+
+        Self::new(samples)
+    }
+
+    fn is_regression_from(&self, baseline: &Stats) -> bool {
+        let slower = self.median.as_secs_f64() > baseline.median.as_secs_f64() * REGRESSION_RATIO;
+        let significant = self.median.saturating_sub(baseline.median).as_secs_f64()
+            > baseline.stddev.as_secs_f64() * REGRESSION_DEVIATIONS;
+
+        slower && significant
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mean {:>10.2?}   median {:>10.2?}   σ {:>10.2?}",
+            self.mean, self.median, self.stddev
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct Baseline(HashMap<Key, Stats>);
+
+impl Baseline {
+    fn path() -> PathBuf {
+        manifest_dir().join("bench-baseline.tsv")
+    }
+
+    fn load() -> eyre::Result<Self> {
+        let Ok(contents) = fs::read_to_string(Self::path()) else {
+            return Ok(Self::default());
+        };
+
+        let mut baseline = HashMap::new();
+        for line in contents.lines().filter(|line| !line.starts_with('#')) {
+            let fields = line.split('\t').collect::<Vec<_>>();
+            let &[day, part, stage, mean, median, stddev] = fields.as_slice() else {
+                eyre::bail!("Malformed baseline line: {line:?}");
+            };
+
+            let nanos = |field: &str| -> eyre::Result<Duration> {
+                Ok(Duration::from_nanos(field.parse()?))
+            };
+
+            let key = Key {
+                day: day.parse()?,
+                part: if part == "-" { None } else { Some(part.parse()?) },
+                stage: Stage::from_name(stage).ok_or_eyre("Unknown stage in baseline")?,
+            };
+
+            let stats = Stats {
+                mean: nanos(mean)?,
+                median: nanos(median)?,
+                stddev: nanos(stddev)?,
+            };
+
+            baseline.insert(key, stats);
+        }
+
+        Ok(Self(baseline))
+    }
+
+    fn save(&self) -> eyre::Result<()> {
+        let mut entries = self.0.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| (key.day, key.part, key.stage.name()));
+
+        let mut output = String::from("# day\tpart\tstage\tmean_ns\tmedian_ns\tstddev_ns\n");
+        for (key, stats) in entries {
+            let part = key.part.map_or("-".to_string(), |part| part.to_string());
+            output += &format!(
+                "{}\t{part}\t{}\t{}\t{}\t{}\n",
+                key.day,
+                key.stage.name(),
+                stats.mean.as_nanos(),
+                stats.median.as_nanos(),
+                stats.stddev.as_nanos(),
+            );
+        }
+
+        fs::write(Self::path(), output)?;
+        Ok(())
+    }
+}
+
+pub fn run(days: &[u8], solve: Solver, inputs: &InputStore, args: &Args) -> eyre::Result<()> {
+    let baseline = Baseline::load()?;
+    let mut results = Baseline::default();
+
+    let mut record = |key: Key, stats: Stats| {
+        let part = key.part.map_or(String::from("    "), |part| format!("part {part}"));
+        let comparison = match baseline.0.get(&key) {
+            None => String::new(),
+            Some(previous) => {
+                let change = stats.median.as_secs_f64() / previous.median.as_secs_f64() - 1.0;
+                let flag = if stats.is_regression_from(previous) {
+                    "  REGRESSION"
+                } else {
+                    ""
+                };
+
+                format!("   ({:+.1}% vs baseline){flag}", change * 100.0)
+            }
+        };
+
+        println!(
+            "day {:02} {part}  {:<16}  {stats}{comparison}",
+            key.day,
+            key.stage.name()
+        );
+
+        results.0.insert(key, stats);
+    };
+
+    for &day in days {
+        let input = inputs.get(day)?;
+
+        if let Some(parse) = crate::solutions::parser(day) {
+            let stats = Stats::measure(args.iterations, || parse(&input));
+            record(Key { day, part: None, stage: Stage::Parse }, stats);
+        }
+
+        let single_thread = if crate::solutions::is_parallel(day) {
+            Some(rayon::ThreadPoolBuilder::new().num_threads(1).build()?)
+        } else {
+            None
+        };
+
+        for part in super::parts() {
+            // Warm-up, and makes sure there's something to measure.
+            let Some(run) = run_part(solve, day, part, &input) else {
+                continue;
+            };
+
+            if let Err(error) = run.answer {
+                eprintln!("day {day:02} part {part}  failed, skipping: {error}");
+                continue;
+            }
+
+            let measure = || {
+                Stats::measure(args.iterations, || {
+                    black_box(solve(day, part, black_box(&input)));
+                })
+            };
+
+            let part = Some(part);
+            record(Key { day, part, stage: Stage::Solve }, measure());
+
+            if let Some(pool) = &single_thread {
+                let stage = Stage::SingleThreaded;
+                record(Key { day, part, stage }, pool.install(measure));
+            }
+        }
+    }
+
+    if args.save_baseline || baseline.0.is_empty() {
+        // Keep the entries of days that weren't run this time.
+        let mut merged = baseline.0;
+        merged.extend(results.0);
+        Baseline(merged).save()?;
+        eprintln!("Saved baseline to `{}`", Baseline::path().display());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stats() {
+    let samples = [4, 1, 3, 2, 5].map(Duration::from_millis).to_vec();
+    let stats = Stats::new(samples);
+
+    assert_eq!(stats.median, Duration::from_millis(3));
+    assert_eq!(stats.mean, Duration::from_millis(3));
+    assert!((stats.stddev.as_secs_f64() - 2f64.sqrt() / 1000.0).abs() < 1e-9);
+
+    let slower = Stats {
+        median: Duration::from_millis(10),
+        ..stats
+    };
+    assert!(slower.is_regression_from(&stats));
+    assert!(!stats.is_regression_from(&slower));
+}
@@ -0,0 +1,116 @@
+//! The `generate-docs` feature: a Markdown report per day with its source,
+//! examples and answers, written to `docs/`.
+
+use std::{fmt::Write as _, fs};
+
+use color_eyre::eyre;
+
+use super::{examples, inputs::InputStore, manifest_dir, run_part, Run, Solver};
+
+pub fn generate(days: &[u8], solve: Solver, inputs: &InputStore) -> eyre::Result<()> {
+    let out_dir = manifest_dir().join("docs");
+    fs::create_dir_all(&out_dir)?;
+
+    let mut index =
+        String::from("# Advent of Code 2024\n\n| Day | Part 1 | Part 2 |\n|---|---|---|\n");
+
+    for &day in days {
+        let (report, answers) = day_report(day, solve, inputs)?;
+        fs::write(out_dir.join(format!("day{day:02}.md")), report)?;
+
+        let [part1, part2] = answers.map(|run| run.map_or(String::new(), |run| cell(&run)));
+        writeln!(index, "| [{day}](day{day:02}.md) | {part1} | {part2} |")?;
+
+        eprintln!("Generated docs for day {day}");
+    }
+
+    fs::write(out_dir.join("README.md"), index)?;
+
+    Ok(())
+}
+
+/// The Markdown of `day`, and its answers to the real input.
+fn day_report(
+    day: u8,
+    solve: Solver,
+    inputs: &InputStore,
+) -> eyre::Result<(String, [Option<Run>; 2])> {
+    let mut report = String::new();
+    writeln!(report, "# Day {day}\n")?;
+    writeln!(report, "[Puzzle](https://adventofcode.com/2024/day/{day})\n")?;
+
+    let input = inputs.get(day);
+    let examples = examples::examples(day);
+    let mut answers = [None, None];
+
+    for part in super::parts() {
+        let examples = examples.iter().filter(|example| example.part == part);
+        let mut rows = String::new();
+        let mut inputs = String::new();
+
+        for (i, example) in examples.enumerate() {
+            let name = format!("Example {}", i + 1);
+            writeln!(inputs, "<details><summary>{name}</summary>\n")?;
+            writeln!(inputs, "```text\n{}\n```\n", example.input.trim_end())?;
+            writeln!(inputs, "</details>\n")?;
+
+            // Without an expected answer there's nothing to check, and some
+            // examples don't even apply (e.g. a different grid size).
+            let Some(expected) = &example.expected else {
+                continue;
+            };
+
+            let Some(run) = run_part(solve, day, part, &example.input) else {
+                continue;
+            };
+
+            let check = match &run.answer {
+                Ok(answer) if answer == expected => "✅",
+                _ => "❌",
+            };
+
+            writeln!(
+                rows,
+                "| {name} | {} | {expected} {check} | {:?} |",
+                cell(&run),
+                run.duration
+            )?;
+        }
+
+        match &input {
+            Ok(input) => {
+                if let Some(run) = run_part(solve, day, part, input) {
+                    writeln!(rows, "| Input | {} | | {:?} |", cell(&run), run.duration)?;
+                    answers[part as usize - 1] = Some(run);
+                }
+            }
+            Err(_) => writeln!(rows, "| Input | *missing* | | |")?,
+        }
+
+        if rows.is_empty() {
+            continue;
+        }
+
+        writeln!(report, "## Part {part}\n")?;
+        writeln!(report, "| | Answer | Expected | Time |\n|---|---|---|---|")?;
+        writeln!(report, "{rows}")?;
+        write!(report, "{inputs}")?;
+    }
+
+    writeln!(report, "## Solution\n")?;
+    for (path, source) in examples::source_files(day)? {
+        let path = path.strip_prefix(manifest_dir()).unwrap_or(&path);
+        writeln!(report, "### `{}`\n", path.display())?;
+        writeln!(report, "```rust\n{}\n```\n", source.trim_end())?;
+    }
+
+    Ok((report, answers))
+}
+
+/// An answer as it should appear in a table cell.
+fn cell(run: &Run) -> String {
+    match &run.answer {
+        Ok(answer) => format!("`{answer}`"),
+        Err(error) => error.replace('|', "\\|").replace('\n', " "),
+    }
+}
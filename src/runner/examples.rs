@@ -0,0 +1,48 @@
+//! Reads the examples of a day back out of its source.
+//!
+//! The `elvish` macros don't expose what they were given at runtime, so
+//! `build.rs` looks at the `#[elvish::solution]` attributes and
+//! `elvish::example!` blocks directly (see `scrape.rs`) and bakes what it finds
+//! into the binary.
+
+use std::path::PathBuf;
+
+use color_eyre::eyre::{self, WrapErr as _};
+
+use super::manifest_dir;
+
+// Only `build.rs` uses this, we just run its test.
+#[cfg(test)]
+mod scrape;
+mod sources;
+
+/// Every example as `(day, part, input, expected)`, generated by `build.rs`.
+static EXAMPLES: &[(u8, u8, &str, Option<&str>)] =
+    include!(concat!(env!("OUT_DIR"), "/examples.rs"));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub part: u8,
+    pub input: String,
+    /// The answer given in `#[elvish::solution(example = ...)]`, if any.
+    pub expected: Option<String>,
+}
+
+/// The source files that make up `day`, in path order.
+pub fn source_files(day: u8) -> eyre::Result<Vec<(PathBuf, String)>> {
+    sources::source_files(&manifest_dir().join("src/solutions"), day)
+        .wrap_err_with(|| format!("Couldn't find the source of day {day}"))
+}
+
+/// All the examples of `day`, paired with their expected answers.
+pub fn examples(day: u8) -> Vec<Example> {
+    EXAMPLES
+        .iter()
+        .filter(|(example_day, ..)| *example_day == day)
+        .map(|&(_, part, input, expected)| Example {
+            part,
+            input: input.to_string(),
+            expected: expected.map(str::to_string),
+        })
+        .collect()
+}
@@ -0,0 +1,213 @@
+//! Finds the examples in the source of a day, for `build.rs` to bake into the
+//! binary. The binary itself only builds this for the test, so it only uses
+//! `std` and `regex`.
+
+use std::{path::PathBuf, sync::LazyLock};
+
+use regex::Regex;
+
+/// The examples in the `sources` of a day, as `(part, input, expected)`.
+pub fn examples(sources: &[(PathBuf, String)]) -> Vec<(u8, String, Option<String>)> {
+    let mut expected = [Vec::new(), Vec::new()];
+    let mut inputs = [Vec::new(), Vec::new()];
+    for (_, source) in sources {
+        for (part, answers) in expected_answers(source) {
+            expected[part as usize - 1].extend(answers);
+        }
+
+        for (part, input) in example_inputs(source) {
+            let parts = match part {
+                Some(part) => part..=part,
+                None => 1..=2,
+            };
+
+            for part in parts {
+                inputs[part as usize - 1].push(input.clone());
+            }
+        }
+    }
+
+    (1..=2)
+        .flat_map(|part| {
+            let expected = &expected[part as usize - 1];
+            inputs[part as usize - 1]
+                .iter()
+                .enumerate()
+                .map(move |(i, input)| (part, input.clone(), expected.get(i).cloned()))
+        })
+        .collect()
+}
+
+/// The `example = ...` answers of each `#[elvish::solution]` in `source`.
+fn expected_answers(source: &str) -> Vec<(u8, Vec<String>)> {
+    static SOLUTION: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?s)#\[elvish::solution\((?<args>.*?)\)\]\s*(?:pub\s+)?fn\s+part(?<part>[12])")
+            .unwrap()
+    });
+
+    static EXAMPLE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"example\s*=\s*(?:\[(?<list>[^\]]*)\]|"(?<string>[^"]*)"|(?<value>[^,\s)]+))"#)
+            .unwrap()
+    });
+
+    SOLUTION
+        .captures_iter(source)
+        .map(|solution| {
+            let part = solution["part"].parse().unwrap();
+            let answers = match EXAMPLE.captures(&solution["args"]) {
+                None => Vec::new(),
+                Some(example) => {
+                    if let Some(list) = example.name("list") {
+                        list.as_str()
+                            .split(',')
+                            .map(|answer| answer.trim().trim_matches('"').to_string())
+                            .filter(|answer| !answer.is_empty())
+                            .collect()
+                    } else {
+                        let answer = example.name("string").or(example.name("value")).unwrap();
+                        vec![answer.as_str().to_string()]
+                    }
+                }
+            };
+
+            (part, answers)
+        })
+        .collect()
+}
+
+/// The inputs of every `elvish::example!` in `source`, with the part they
+/// belong to (`None` means both).
+fn example_inputs(source: &str) -> Vec<(Option<u8>, String)> {
+    const MACRO: &str = "elvish::example!(";
+
+    let mut output = Vec::new();
+    for (start, _) in source.match_indices(MACRO) {
+        let mut rest = &source[start + MACRO.len()..];
+        let mut part = None;
+
+        loop {
+            rest = rest.trim_start();
+
+            if let Some(after) = rest.strip_prefix("//") {
+                rest = after.split_once('\n').map_or("", |(_, after)| after);
+            } else if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("part1:") {
+                (part, rest) = (Some(1), after);
+            } else if let Some(after) = rest.strip_prefix("part2:") {
+                (part, rest) = (Some(2), after);
+            } else if let Some(after) = rest.strip_prefix('"') {
+                let (literal, after) = string_literal(after);
+                output.push((part.take(), indoc(&literal)));
+                rest = after;
+            } else {
+                // Either the closing paren or something we don't understand.
+                break;
+            }
+        }
+    }
+
+    output
+}
+
+/// Unescapes a string literal, starting right after the opening quote. Returns
+/// the contents and what comes after the closing quote.
+fn string_literal(source: &str) -> (String, &str) {
+    let mut output = String::new();
+    let mut chars = source.char_indices();
+
+    while let Some((i, char)) = chars.next() {
+        match char {
+            '"' => return (output, &source[i + 1..]),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('n') => output.push('\n'),
+                Some('t') => output.push('\t'),
+                Some('\n') => {
+                    // Line continuation, skips the leading whitespace of the next line.
+                    let rest = chars.as_str();
+                    let skip = rest.len() - rest.trim_start().len();
+                    if skip > 0 {
+                        chars.nth(skip - 1);
+                    }
+                }
+                Some(other) => output.push(other),
+                None => break,
+            },
+            _ => output.push(char),
+        }
+    }
+
+    (output, "")
+}
+
+/// Same as `indoc!`: removes the leading newline and the common indentation.
+fn indoc(literal: &str) -> String {
+    let literal = literal.strip_prefix('\n').unwrap_or(literal);
+
+    let indent = literal
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut output = String::with_capacity(literal.len());
+    for (i, line) in literal.split('\n').enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+
+        output.push_str(line.get(indent..).unwrap_or(line.trim_start()));
+    }
+
+    output
+}
+
+#[test]
+fn reads_examples() {
+    let source = r#"
+        #[elvish::solution(day = 16, example = [7036, 11048])]
+        fn part1(input: &str) -> u64 { 0 }
+
+        #[elvish::solution(day = 16)]
+        pub fn part2(input: &str) -> String { String::new() }
+
+        elvish::example!(
+            part1: "
+                #.#
+                #S)
+            ",
+
+            // A comment
+            part1: "second",
+            part2: "third",
+        );
+    "#;
+
+    assert_eq!(
+        expected_answers(source),
+        [
+            (1, vec!["7036".to_string(), "11048".to_string()]),
+            (2, vec![])
+        ]
+    );
+
+    assert_eq!(
+        example_inputs(source),
+        [
+            (Some(1), "#.#\n#S)\n".to_string()),
+            (Some(1), "second".to_string()),
+            (Some(2), "third".to_string()),
+        ]
+    );
+
+    let string = |s: &str| s.to_string();
+    assert_eq!(
+        examples(&[(PathBuf::from("day16.rs"), source.to_string())]),
+        [
+            (1, string("#.#\n#S)\n"), Some(string("7036"))),
+            (1, string("second"), Some(string("11048"))),
+            (2, string("third"), None),
+        ]
+    );
+}
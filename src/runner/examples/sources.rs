@@ -0,0 +1,48 @@
+//! Finds the source files of a day. This is shared with `build.rs`, so it only
+//! uses `std`.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The source files that make up `day`, in path order.
+pub fn source_files(solutions: &Path, day: u8) -> io::Result<Vec<(PathBuf, String)>> {
+    let single_file = solutions.join(format!("day{day:02}.rs"));
+
+    let directory = solutions.join(format!("day{day:02}"));
+
+    // A day is either `dayNN.rs`, `dayNN/mod.rs` or `dayNN.rs` with its
+    // submodules in `dayNN/`.
+    let mut paths = Vec::new();
+    if single_file.exists() {
+        paths.push(single_file);
+    }
+
+    if directory.is_dir() || paths.is_empty() {
+        let mut queue = vec![directory];
+        while let Some(dir) = queue.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    queue.push(path);
+                } else if path.extension().is_some_and(|ext| ext == "rs")
+                    // Files that can't be modules (like `failed-attempt.rs`)
+                    // aren't part of the day.
+                    && !path.file_stem().is_some_and(|stem| stem.to_string_lossy().contains('-'))
+                {
+                    paths.push(path);
+                }
+            }
+        }
+    }
+
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let source = fs::read_to_string(&path)?;
+            Ok((path, source))
+        })
+        .collect()
+}
@@ -0,0 +1,265 @@
+//! Puzzle inputs, cached in `inputs/dayNN.txt`.
+//!
+//! Inputs that aren't cached yet are downloaded through a [`Fetcher`], and
+//! their checksum is recorded in `inputs/checksums` so that a cached input
+//! that got truncated or edited by accident doesn't go unnoticed.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, WrapErr as _};
+
+use super::{manifest_dir, Args};
+
+const YEAR: u16 = 2024;
+
+/// FNV-1a, which (unlike `DefaultHasher`) is stable across Rust versions.
+///
+/// Trailing whitespace is ignored, since it depends on how the input was saved.
+pub fn checksum(input: &str) -> u64 {
+    input
+        .trim_end()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Somewhere to get inputs from when they aren't cached.
+pub trait Fetcher {
+    fn fetch(&self, day: u8) -> eyre::Result<String>;
+}
+
+/// Downloads inputs from the Advent of Code website (or anything that serves
+/// the same paths), authenticating with a session cookie.
+pub struct HttpFetcher {
+    pub base_url: String,
+    pub session: Option<String>,
+}
+
+impl HttpFetcher {
+    /// Reads the session cookie from `AOC_SESSION`. The site can be overridden
+    /// with `AOC_URL`, to point it at a local stand-in.
+    pub fn from_env() -> Self {
+        HttpFetcher {
+            base_url: std::env::var("AOC_URL")
+                .unwrap_or_else(|_| "https://adventofcode.com".to_string()),
+            session: std::env::var("AOC_SESSION").ok(),
+        }
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, day: u8) -> eyre::Result<String> {
+        let Some(session) = &self.session else {
+            eyre::bail!(
+                "Can't download the input of day {day} without `AOC_SESSION` set (in `.env`)"
+            );
+        };
+
+        let url = format!(
+            "{}/{YEAR}/day/{day}/input",
+            self.base_url.trim_end_matches('/')
+        );
+        let response = ureq::get(&url)
+            .set("Cookie", &format!("session={session}"))
+            .set("User-Agent", "github.com/odilf/advent-of-code-2024")
+            .call();
+
+        match response {
+            Ok(response) => Ok(response.into_string()?),
+            Err(ureq::Error::Status(404, _)) => {
+                eyre::bail!("The input of day {day} isn't available (yet?) at {url}")
+            }
+            Err(ureq::Error::Status(code, _)) => {
+                eyre::bail!("Got status {code} downloading {url}, is `AOC_SESSION` still valid?")
+            }
+            Err(error) => Err(error).wrap_err_with(|| format!("Couldn't download {url}")),
+        }
+    }
+}
+
+/// Never touches the network, for `--offline`.
+pub struct Offline;
+
+impl Fetcher for Offline {
+    fn fetch(&self, day: u8) -> eyre::Result<String> {
+        eyre::bail!("The input of day {day} isn't cached, and we're offline")
+    }
+}
+
+pub struct InputStore {
+    dir: PathBuf,
+    fetcher: Box<dyn Fetcher>,
+}
+
+impl InputStore {
+    pub fn new(dir: impl Into<PathBuf>, fetcher: Box<dyn Fetcher>) -> Self {
+        InputStore {
+            dir: dir.into(),
+            fetcher,
+        }
+    }
+
+    pub fn from_args(args: &Args) -> Self {
+        let fetcher: Box<dyn Fetcher> = if args.offline {
+            Box::new(Offline)
+        } else {
+            Box::new(HttpFetcher::from_env())
+        };
+
+        Self::new(manifest_dir().join("inputs"), fetcher)
+    }
+
+    fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{day:02}.txt"))
+    }
+
+    fn checksums_path(&self) -> PathBuf {
+        self.dir.join("checksums")
+    }
+
+    /// The input of `day`, from the cache if possible.
+    pub fn get(&self, day: u8) -> eyre::Result<String> {
+        let path = self.path(day);
+        let mut checksums = read_checksums(&self.checksums_path())?;
+
+        if path.exists() {
+            let input = fs::read_to_string(&path)?;
+
+            if let Some(&expected) = checksums.get(&day) {
+                if checksum(&input) != expected {
+                    eyre::bail!(
+                        "The checksum of `{}` doesn't match the one it was downloaded with. \
+                        Delete it to download it again.",
+                        path.display()
+                    );
+                }
+            }
+
+            return Ok(input);
+        }
+
+        let input = self.fetcher.fetch(day)?;
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&path, &input)?;
+        checksums.insert(day, checksum(&input));
+        write_checksums(&self.checksums_path(), &checksums)?;
+
+        Ok(input)
+    }
+}
+
+fn read_checksums(path: &Path) -> eyre::Result<BTreeMap<u8, u64>> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Ok(BTreeMap::new());
+    };
+
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (hash, file) = line
+                .split_once("  ")
+                .ok_or_else(|| eyre::eyre!("Malformed checksum line: {line:?}"))?;
+
+            let day = file
+                .strip_prefix("day")
+                .and_then(|file| file.strip_suffix(".txt"))
+                .ok_or_else(|| eyre::eyre!("Unexpected file in checksums: {file:?}"))?;
+
+            Ok((day.parse()?, u64::from_str_radix(hash, 16)?))
+        })
+        .collect()
+}
+
+fn write_checksums(path: &Path, checksums: &BTreeMap<u8, u64>) -> eyre::Result<()> {
+    let contents = checksums
+        .iter()
+        .map(|(day, hash)| format!("{hash:016x}  day{day:02}.txt\n"))
+        .collect::<String>();
+
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// A stand-in for the Advent of Code website that serves a single input.
+#[cfg(test)]
+pub struct MockServer {
+    pub url: String,
+    handle: std::thread::JoinHandle<String>,
+}
+
+#[cfg(test)]
+impl MockServer {
+    /// Answers one request with `body`.
+    pub fn serve_once(body: &'static str) -> Self {
+        use std::io::{BufRead as _, BufReader, Write as _};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request += &line;
+            }
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+
+            request
+        });
+
+        MockServer { url, handle }
+    }
+
+    /// The request it received.
+    pub fn request(self) -> String {
+        self.handle.join().unwrap()
+    }
+}
+
+#[test]
+fn fetch_and_cache() {
+    let dir = std::env::temp_dir().join(format!("aoc-2024-inputs-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let server = MockServer::serve_once("1 2 3\n");
+    let fetcher = HttpFetcher {
+        base_url: server.url.clone(),
+        session: Some("cookie".to_string()),
+    };
+
+    let store = InputStore::new(&dir, Box::new(fetcher));
+    assert_eq!(store.get(7).unwrap(), "1 2 3\n");
+
+    let request = server.request();
+    assert!(request.starts_with("GET /2024/day/7/input "));
+    assert!(request.to_lowercase().contains("cookie: session=cookie"));
+
+    // Now it's cached, so it doesn't need the network.
+    let offline = InputStore::new(&dir, Box::new(Offline));
+    assert_eq!(offline.get(7).unwrap(), "1 2 3\n");
+    assert!(offline.get(8).is_err());
+
+    fs::write(dir.join("day07.txt"), "1 2").unwrap();
+    assert!(offline.get(7).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,30 @@
+//! `--inspect <DIR>`: writes whatever helps debug a day (pictures, traces,
+//! graphs...) to a directory, instead of running it.
+//!
+//! The solutions themselves stay pure. Each day that has something to show
+//! registers an `inspect` function in [`crate::solutions::inspector`], which
+//! gets the real input and the directory. Some of them also read options from
+//! files in that directory, see the README.
+
+use std::{fs, path::Path};
+
+use color_eyre::eyre::{self, WrapErr as _};
+
+use super::inputs::InputStore;
+
+pub fn run(days: &[u8], inputs: &InputStore, dir: &Path) -> eyre::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    for &day in days {
+        let Some(inspect) = crate::solutions::inspector(day) else {
+            eprintln!("Day {day} has nothing to inspect");
+            continue;
+        };
+
+        let input = inputs.get(day)?;
+        inspect(&input, dir).wrap_err_with(|| format!("Couldn't inspect day {day}"))?;
+        eprintln!("Inspected day {day}, see `{}`", dir.display());
+    }
+
+    Ok(())
+}
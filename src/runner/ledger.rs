@@ -0,0 +1,264 @@
+//! A record of the answers we've submitted, in `answers.ledger`.
+//!
+//! Accepted answers are keyed by day, part and the checksum of the input, so a
+//! different input doesn't get checked against the wrong answer. Wrong
+//! submissions are kept too, so they get flagged before being submitted again.
+
+use std::{fmt, fs, path::PathBuf};
+
+use color_eyre::eyre;
+
+use super::manifest_dir;
+
+const VERSION: u32 = 1;
+const HEADER: &str = "day\tpart\tinput\tstatus\tanswer\tnote";
+
+/// Marks entries that were recorded without knowing the input, which match any.
+const ANY_INPUT: &str = "-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Accepted,
+    Wrong,
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Wrong => "wrong",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub day: u8,
+    pub part: u8,
+    /// `None` if it applies to any input.
+    pub input_hash: Option<u64>,
+    pub status: Status,
+    pub answer: String,
+    pub note: String,
+}
+
+impl Entry {
+    fn applies_to(&self, day: u8, part: u8, input_hash: u64) -> bool {
+        self.day == day
+            && self.part == part
+            && self.input_hash.is_none_or(|hash| hash == input_hash)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    Wrong { expected: String },
+    KnownWrong { note: String },
+    New,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Correct => write!(f, "correct"),
+            Self::Wrong { expected } => write!(f, "WRONG, expected {expected}"),
+            Self::KnownWrong { note } if note.is_empty() => write!(f, "WRONG, already submitted"),
+            Self::KnownWrong { note } => write!(f, "WRONG, already submitted ({note})"),
+            Self::New => write!(f, "new"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ledger {
+    pub entries: Vec<Entry>,
+}
+
+impl Ledger {
+    pub fn path() -> PathBuf {
+        manifest_dir().join("answers.ledger")
+    }
+
+    pub fn load() -> eyre::Result<Self> {
+        match fs::read_to_string(Self::path()) {
+            Ok(contents) => contents.parse(),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        fs::write(Self::path(), self.to_string())?;
+        Ok(())
+    }
+
+    pub fn check(&self, day: u8, part: u8, input_hash: u64, answer: &str) -> Verdict {
+        let mut entries = self
+            .entries
+            .iter()
+            .filter(|entry| entry.applies_to(day, part, input_hash));
+
+        if let Some(wrong) = entries
+            .clone()
+            .find(|entry| entry.status == Status::Wrong && entry.answer == answer)
+        {
+            return Verdict::KnownWrong {
+                note: wrong.note.clone(),
+            };
+        }
+
+        match entries.find(|entry| entry.status == Status::Accepted) {
+            Some(accepted) if accepted.answer == answer => Verdict::Correct,
+            Some(accepted) => Verdict::Wrong {
+                expected: accepted.answer.clone(),
+            },
+            None => Verdict::New,
+        }
+    }
+
+    /// Records `answer` as the accepted one, replacing any previous one.
+    pub fn accept(&mut self, day: u8, part: u8, input_hash: u64, answer: &str) {
+        self.entries.retain(|entry| {
+            !(entry.status == Status::Accepted
+                && entry.day == day
+                && entry.part == part
+                && entry.input_hash == Some(input_hash))
+        });
+
+        self.entries.push(Entry {
+            day,
+            part,
+            input_hash: Some(input_hash),
+            status: Status::Accepted,
+            answer: answer.to_string(),
+            note: String::new(),
+        });
+    }
+
+    pub fn reject(&mut self, day: u8, part: u8, input_hash: u64, answer: &str, note: &str) {
+        if let Verdict::KnownWrong { .. } = self.check(day, part, input_hash, answer) {
+            return;
+        }
+
+        self.entries.push(Entry {
+            day,
+            part,
+            input_hash: Some(input_hash),
+            status: Status::Wrong,
+            answer: answer.to_string(),
+            note: note.to_string(),
+        });
+    }
+}
+
+impl fmt::Display for Ledger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# Answers submitted to Advent of Code. Update with `--accept` and `--reject`."
+        )?;
+        writeln!(f, "version {VERSION}")?;
+        writeln!(f, "{HEADER}")?;
+
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| (entry.day, entry.part, entry.status.name()));
+
+        for entry in entries {
+            let input = match entry.input_hash {
+                Some(hash) => format!("{hash:016x}"),
+                None => ANY_INPUT.to_string(),
+            };
+
+            writeln!(
+                f,
+                "{}\t{}\t{input}\t{}\t{}\t{}",
+                entry.day,
+                entry.part,
+                entry.status.name(),
+                entry.answer,
+                entry.note
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Ledger {
+    type Err = eyre::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().filter(|line| !line.starts_with('#'));
+
+        match lines.next() {
+            Some(line) if line == format!("version {VERSION}") => (),
+            Some(line) => eyre::bail!("Unsupported ledger version: {line:?}"),
+            None => return Ok(Self::default()),
+        }
+
+        let mut entries = Vec::new();
+        for line in lines.filter(|&line| line != HEADER && !line.is_empty()) {
+            let fields = line.split('\t').collect::<Vec<_>>();
+            let &[day, part, input, status, answer, note] = fields.as_slice() else {
+                eyre::bail!("Malformed ledger line: {line:?}");
+            };
+
+            let status = match status {
+                "accepted" => Status::Accepted,
+                "wrong" => Status::Wrong,
+                _ => eyre::bail!("Unknown status {status:?} in ledger line {line:?}"),
+            };
+
+            entries.push(Entry {
+                day: day.parse()?,
+                part: part.parse()?,
+                input_hash: match input {
+                    ANY_INPUT => None,
+                    hash => Some(u64::from_str_radix(hash, 16)?),
+                },
+                status,
+                answer: answer.to_string(),
+                note: note.to_string(),
+            });
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+#[test]
+fn check_answers() {
+    use super::inputs::checksum;
+
+    let input = checksum("1 2 3\n");
+    assert_eq!(input, checksum("1 2 3"));
+    assert_ne!(input, checksum("1 2 4"));
+
+    let mut ledger = Ledger::default();
+    assert_eq!(ledger.check(17, 2, input, "42"), Verdict::New);
+
+    ledger.reject(17, 2, input, "601201576113503", "Too high");
+    ledger.accept(17, 2, input, "42");
+
+    assert_eq!(ledger.check(17, 2, input, "42"), Verdict::Correct);
+    assert_eq!(
+        ledger.check(17, 2, input, "43"),
+        Verdict::Wrong {
+            expected: "42".to_string()
+        }
+    );
+    assert_eq!(
+        ledger.check(17, 2, input, "601201576113503"),
+        Verdict::KnownWrong {
+            note: "Too high".to_string()
+        }
+    );
+
+    // Other inputs and parts aren't affected.
+    assert_eq!(ledger.check(17, 2, checksum("other"), "43"), Verdict::New);
+    assert_eq!(ledger.check(17, 1, input, "42"), Verdict::New);
+
+    let roundtrip = ledger.to_string().parse::<Ledger>().unwrap();
+    assert_eq!(roundtrip.to_string(), ledger.to_string());
+}
@@ -0,0 +1,201 @@
+//! How the results of a run get printed, set with `--format`.
+
+use std::fmt;
+
+use color_eyre::eyre;
+use serde::Serialize;
+
+use super::{examples::Example, ledger::Verdict, run_part, Run, Solver};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// One line per part, for humans.
+    #[default]
+    Text,
+    /// A single JSON array with every part, printed at the end.
+    Json,
+    /// A JSON object per part, printed as soon as it's done.
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerType {
+    Integer,
+    Text,
+}
+
+impl AnswerType {
+    fn of(answer: &str) -> Self {
+        if answer.parse::<i128>().is_ok() {
+            Self::Integer
+        } else {
+            Self::Text
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExampleCheck {
+    /// Examples weren't run (see `--examples`).
+    Skipped,
+    /// There are no examples with an expected answer.
+    None,
+    Passed,
+    Failed,
+}
+
+impl ExampleCheck {
+    /// Runs every example of `part` that has an expected answer.
+    pub fn run(examples: &[Example], solve: Solver, day: u8, part: u8) -> Self {
+        let mut output = Self::None;
+
+        let examples = examples.iter().filter(|example| example.part == part);
+        for example in examples {
+            let Some(expected) = &example.expected else {
+                continue;
+            };
+
+            let Some(run) = run_part(solve, day, part, &example.input) else {
+                continue;
+            };
+
+            if run.answer.as_ref() != Ok(expected) {
+                return Self::Failed;
+            }
+
+            output = Self::Passed;
+        }
+
+        output
+    }
+}
+
+/// Everything about a single run of a day's part.
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: Option<String>,
+    pub answer_type: Option<AnswerType>,
+    pub duration_ns: Option<u64>,
+    pub examples: ExampleCheck,
+    pub ledger: Option<Verdict>,
+    pub error: Option<String>,
+}
+
+impl Record {
+    pub fn new(
+        day: u8,
+        part: u8,
+        run: &Run,
+        examples: ExampleCheck,
+        ledger: Option<Verdict>,
+    ) -> Self {
+        let (answer, error) = match &run.answer {
+            Ok(answer) => (Some(answer.clone()), None),
+            Err(error) => (None, Some(error.clone())),
+        };
+
+        Record {
+            day,
+            part,
+            answer_type: answer.as_deref().map(AnswerType::of),
+            answer,
+            duration_ns: Some(run.duration.as_nanos() as u64),
+            examples,
+            ledger,
+            error,
+        }
+    }
+
+    /// A part that couldn't run at all.
+    pub fn error(day: u8, part: u8, error: &eyre::Report) -> Self {
+        Record {
+            day,
+            part,
+            answer: None,
+            answer_type: None,
+            duration_ns: None,
+            examples: ExampleCheck::Skipped,
+            ledger: None,
+            error: Some(format!("{error:#}")),
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Day {} part {}: ", self.day, self.part)?;
+
+        match (&self.answer, &self.error) {
+            (Some(answer), _) => write!(f, "{answer}")?,
+            (None, Some(error)) => write!(f, "{error}")?,
+            (None, None) => write!(f, "no answer")?,
+        }
+
+        if let Some(duration) = self.duration_ns {
+            write!(f, " ({:.2?})", std::time::Duration::from_nanos(duration))?;
+        }
+
+        if let Some(verdict) = &self.ledger {
+            write!(f, " [{verdict}]")?;
+        }
+
+        match self.examples {
+            ExampleCheck::Passed => write!(f, " [examples passed]"),
+            ExampleCheck::Failed => write!(f, " [EXAMPLES FAILED]"),
+            ExampleCheck::Skipped | ExampleCheck::None => Ok(()),
+        }
+    }
+}
+
+/// Prints records in some `Format`.
+pub struct Printer {
+    format: Format,
+    pending: Vec<Record>,
+}
+
+impl Printer {
+    pub fn new(format: Format) -> Self {
+        Printer {
+            format,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn print(&mut self, record: Record) -> eyre::Result<()> {
+        match self.format {
+            Format::Text => println!("{record}"),
+            Format::Ndjson => println!("{}", serde_json::to_string(&record)?),
+            Format::Json => self.pending.push(record),
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> eyre::Result<()> {
+        if self.format == Format::Json {
+            println!("{}", serde_json::to_string_pretty(&self.pending)?);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn json_record() {
+    let run = Run {
+        answer: Ok("42".to_string()),
+        duration: std::time::Duration::from_micros(1500),
+    };
+
+    let record = Record::new(1, 2, &run, ExampleCheck::Passed, Some(Verdict::New));
+    assert_eq!(
+        serde_json::to_string(&record).unwrap(),
+        r#"{"day":1,"part":2,"answer":"42","answer_type":"integer","duration_ns":1500000,"examples":"passed","ledger":{"status":"new"},"error":null}"#
+    );
+
+    assert_eq!(record.to_string(), "Day 1 part 2: 42 (1.50ms) [new] [examples passed]");
+}
@@ -0,0 +1,131 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre;
+
+/// Puzzles unlock at midnight EST, which is UTC-5.
+const PUZZLE_UTC_OFFSET: i64 = -5 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// The current date in the puzzle's timezone.
+    pub fn puzzle_today() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System clock should be after 1970");
+
+        Self::from_unix(now.as_secs() as i64 + PUZZLE_UTC_OFFSET)
+    }
+
+    /// Converts seconds since the epoch to a civil date, using Howard Hinnant's
+    /// `civil_from_days` algorithm.
+    fn from_unix(seconds: i64) -> Self {
+        let days = seconds.div_euclid(24 * 60 * 60) + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        };
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+        Date {
+            year,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    /// The puzzle day of `YEAR` that unlocked on this date, if any.
+    fn puzzle_day<const YEAR: u16>(&self) -> Option<u8> {
+        let in_event = self.year == YEAR as i64 && self.month == 12 && (1..=25).contains(&self.day);
+        in_event.then_some(self.day)
+    }
+}
+
+/// Narrows down `available` days to the one that should run on `date`.
+///
+/// During the event that's the puzzle that unlocked today, and it's an error if
+/// it isn't implemented yet. Outside of it, it's the newest implemented day.
+pub fn select<const YEAR: u16>(available: &[u8], date: Date) -> eyre::Result<Vec<u8>> {
+    if let Some(today) = date.puzzle_day::<YEAR>() {
+        if !available.contains(&today) {
+            eyre::bail!(
+                "Today is day {today}, but there's no `day{today:02}` module. \
+                Create `src/solutions/day{today:02}.rs` or enable its feature (`d{today:02}`)."
+            );
+        }
+
+        return Ok(vec![today]);
+    }
+
+    let Some(&newest) = available.iter().max() else {
+        eyre::bail!("There are no days available to run");
+    };
+
+    eprintln!("Day {newest} is the newest implemented day, running that one.");
+
+    Ok(vec![newest])
+}
+
+#[test]
+fn unix_to_date() {
+    assert_eq!(
+        Date::from_unix(0),
+        Date {
+            year: 1970,
+            month: 1,
+            day: 1
+        }
+    );
+
+    // 2024-12-17T05:00:00Z, which is midnight in the puzzle's timezone.
+    let unlock = 1_734_411_600;
+    assert_eq!(Date::from_unix(unlock + PUZZLE_UTC_OFFSET).day, 17);
+    assert_eq!(Date::from_unix(unlock - 1 + PUZZLE_UTC_OFFSET).day, 16);
+
+    assert_eq!(
+        Date::from_unix(951_782_400), // Leap day
+        Date {
+            year: 2000,
+            month: 2,
+            day: 29
+        }
+    );
+}
+
+#[test]
+fn select_days() {
+    let available = [1, 2, 3, 17];
+    let date = |year, month, day| Date { year, month, day };
+
+    assert_eq!(
+        select::<2024>(&available, date(2024, 12, 17)).unwrap(),
+        [17]
+    );
+    assert!(select::<2024>(&available, date(2024, 12, 18)).is_err());
+
+    assert_eq!(
+        select::<2024>(&available, date(2024, 11, 30)).unwrap(),
+        [17]
+    );
+    assert_eq!(
+        select::<2024>(&available, date(2024, 12, 26)).unwrap(),
+        [17]
+    );
+    assert_eq!(select::<2024>(&available, date(2025, 12, 2)).unwrap(), [17]);
+
+    assert!(select::<2024>(&[], date(2025, 1, 1)).is_err());
+}
@@ -13,3 +13,102 @@ elvish::declare::modules!();
 // ```
 //
 // ...and so on, up to day 25.
+
+use std::{hint::black_box, path::Path};
+
+use color_eyre::eyre;
+
+/// The `parse` function of `day`, if it has one, so that benchmarks can time it
+/// apart from the rest of the solution.
+pub fn parser(day: u8) -> Option<fn(&str)> {
+    let parser: fn(&str) = match day {
+        #[cfg(feature = "day01")]
+        1 => |input| {
+            let _ = black_box(day01::parse(input));
+        },
+        #[cfg(feature = "day04")]
+        4 => |input| {
+            let _ = black_box(day04::parse(input));
+        },
+        #[cfg(feature = "day05")]
+        5 => |input| {
+            let _ = black_box(day05::parse(input));
+        },
+        #[cfg(feature = "day08")]
+        8 => |input| {
+            black_box(day08::parse(input));
+        },
+        #[cfg(feature = "day10")]
+        10 => |input| {
+            let _ = black_box(day10::parse(input));
+        },
+        #[cfg(feature = "day12")]
+        12 => |input| {
+            let _ = black_box(day12::parse(input));
+        },
+        #[cfg(feature = "day14")]
+        14 => |input| {
+            let _ = black_box(day14::parse(input));
+        },
+        #[cfg(feature = "day16")]
+        16 => |input| {
+            let _ = black_box(day16::parse(input));
+        },
+        #[cfg(feature = "day17")]
+        17 => |input| {
+            let _ = black_box(day17::parse(input));
+        },
+        #[cfg(feature = "day18")]
+        18 => |input| {
+            let _ = black_box(day18::parse(input.trim()));
+        },
+        #[cfg(feature = "day19")]
+        19 => |input| {
+            let _ = black_box(day19::parse(input));
+        },
+        #[cfg(feature = "day20")]
+        20 => |input| {
+            let _ = black_box(day20::parse(input));
+        },
+        #[cfg(feature = "day23")]
+        23 => |input| {
+            let _ = black_box(day23::parse(input));
+        },
+        #[cfg(feature = "day24")]
+        24 => |input| {
+            let _ = black_box(day24::parse::parse(input));
+        },
+        #[cfg(feature = "day25")]
+        25 => |input| {
+            let _ = black_box(day25::parse(input));
+        },
+        _ => return None,
+    };
+
+    Some(parser)
+}
+
+/// Whether `day` splits its work across rayon's threads, so benchmarks also
+/// time it on a single thread. Keep this in sync when a day starts or stops
+/// using rayon.
+pub fn is_parallel(day: u8) -> bool {
+    matches!(day, 6 | 7 | 17)
+}
+
+/// The `inspect` function of `day`, if it has one, which writes what helps
+/// debug it to a directory. See `runner::inspect`.
+pub fn inspector(day: u8) -> Option<fn(&str, &Path) -> eyre::Result<()>> {
+    let inspector: fn(&str, &Path) -> eyre::Result<()> = match day {
+        #[cfg(feature = "day14")]
+        14 => day14::inspect,
+        #[cfg(feature = "day15")]
+        15 => day15::inspect,
+        #[cfg(feature = "day17")]
+        17 => day17::inspect,
+        #[cfg(feature = "day24")]
+        24 => day24::graph::inspect,
+        _ => return None,
+    };
+
+    Some(inspector)
+}
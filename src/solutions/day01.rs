@@ -1,34 +1,43 @@
-use nom::{character::complete::space1, sequence::separated_pair};
+use color_eyre::eyre;
+use winnow::{ascii::space1, combinator::separated_pair};
 
-fn parse(input: &str) -> (Vec<i32>, Vec<i32>) {
-    use crate::parse::digit;
+use crate::{
+    answer::Answer,
+    parse::{self, uint},
+};
 
-    input
-        .lines()
-        .map(|line| separated_pair(digit::<i32>, space1, digit::<i32>)(line).unwrap().1)
-        .unzip()
+pub(super) fn parse(input: &str) -> eyre::Result<(Vec<i32>, Vec<i32>)> {
+    let pairs = parse::all(input, parse::lines(separated_pair(uint::<i32>, space1, uint::<i32>)))?;
+
+    Ok(pairs.into_iter().unzip())
 }
 
 #[elvish::solution(day = 1, example = 11)]
-fn part1(input: &str) -> i32 {
-    let (mut left, mut right) = parse(input);
-
-    left.sort_unstable();
-    right.sort_unstable();
-
-    left.iter()
-        .zip(right.iter())
-        .map(|(&a, &b)| (a - b).abs())
-        .sum()
+fn part1(input: &str) -> Answer<i32> {
+    Answer::new(|| {
+        let (mut left, mut right) = parse(input)?;
+
+        left.sort_unstable();
+        right.sort_unstable();
+
+        Ok(left
+            .iter()
+            .zip(right.iter())
+            .map(|(&a, &b)| (a - b).abs())
+            .sum())
+    })
 }
 
 #[elvish::solution(day = 1, example = 31)]
-fn part2(input: &str) -> i32 {
-    let (left, right) = parse(input);
-
-    left.iter()
-        .map(|&a| a * right.iter().filter(|&&b| b == a).count() as i32)
-        .sum()
+fn part2(input: &str) -> Answer<i32> {
+    Answer::new(|| {
+        let (left, right) = parse(input)?;
+
+        Ok(left
+            .iter()
+            .map(|&a| a * right.iter().filter(|&&b| b == a).count() as i32)
+            .sum())
+    })
 }
 
 elvish::example!(
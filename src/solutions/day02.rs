@@ -1,19 +1,30 @@
-fn parse_line(line: &str) -> Vec<i32> {
-    line.split(" ")
-        .map(str::parse::<i32>)
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap()
+use winnow::{
+    error::{StrContext, StrContextValue},
+    Parser as _,
+};
+
+use crate::{
+    answer::Answer,
+    parse::{self, int, ParseError},
+};
+
+fn parse(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    let report = parse::space_list(int::<i32>)
+        .verify(|levels: &Vec<i32>| levels.len() >= 2)
+        .context(StrContext::Label("report"))
+        .context(StrContext::Expected(StrContextValue::Description("at least two levels")));
+
+    parse::all(input, parse::lines(report))
 }
 
 #[elvish::solution(day = 2, example = 2)]
-fn part1(input: &str) -> usize {
-    input
-        .lines()
-        .filter(|&line| {
-            let digits = parse_line(line);
-            valid_list(&digits, (digits[1] - digits[0]).signum())
-        })
-        .count()
+fn part1(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        Ok(parse(input)?
+            .into_iter()
+            .filter(|digits| valid_list(digits, (digits[1] - digits[0]).signum()))
+            .count())
+    })
 }
 
 fn valid_pair(a: i32, b: i32, signum: i32) -> bool {
@@ -29,25 +40,25 @@ fn valid_list(digits: &[i32], signum: i32) -> bool {
 }
 
 #[elvish::solution(day = 2, example = 4)]
-fn part2(input: &str) -> usize {
-    input
-        .lines()
-        .filter(|&line| {
-            let digits = parse_line(line);
-
-            (0..=digits.len()).any(|exclude| {
-                let digits: Vec<_> = digits
-                    .iter()
-                    .cloned()
-                    .enumerate()
-                    .filter(|&(i, _)| i != exclude)
-                    .map(|(_, v)| v)
-                    .collect();
-
-                valid_list(&digits, (digits[1] - digits[0]).signum())
+fn part2(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        Ok(parse(input)?
+            .into_iter()
+            .filter(|digits| {
+                (0..=digits.len()).any(|exclude| {
+                    let digits: Vec<_> = digits
+                        .iter()
+                        .cloned()
+                        .enumerate()
+                        .filter(|&(i, _)| i != exclude)
+                        .map(|(_, v)| v)
+                        .collect();
+
+                    valid_list(&digits, (digits[1] - digits[0]).signum())
+                })
             })
-        })
-        .count()
+            .count())
+    })
 }
 
 elvish::example!(
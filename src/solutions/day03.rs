@@ -1,35 +1,70 @@
-use regex::{Regex, RegexSet};
+use winnow::{
+    combinator::{alt, delimited, repeat, separated_pair},
+    token::any,
+    Parser as _,
+};
+
+use crate::{
+    answer::Answer,
+    parse::{self, uint, ParseError},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Instruction {
+    Mul(i32, i32),
+    Do,
+    Dont,
+}
+
+/// The instructions hidden in the corrupted memory, skipping everything else.
+fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let instruction = alt((
+        "don't".value(Some(Instruction::Dont)),
+        "do".value(Some(Instruction::Do)),
+        delimited("mul(", separated_pair(uint, ',', uint), ')')
+            .map(|(a, b)| Some(Instruction::Mul(a, b))),
+        any.value(None),
+    ));
+
+    parse::winnow(
+        input,
+        input,
+        repeat(0.., instruction)
+            .map(|instructions: Vec<_>| instructions.into_iter().flatten().collect()),
+    )
+}
 
 #[elvish::solution(day = 3, example = 161)]
-fn part1(input: &str) -> i32 {
-    let re = Regex::new(r"mul\((?<a>\d+),(?<b>\d+)\)").unwrap();
-    re.captures_iter(input)
-        .map(|cap| {
-            let a: i32 = cap["a"].parse().unwrap();
-            let b: i32 = cap["b"].parse().unwrap();
-            a * b
-        })
-        .sum()
+fn part1(input: &str) -> Answer<i32> {
+    Answer::new(|| {
+        Ok(parse(input)?
+            .into_iter()
+            .map(|instruction| match instruction {
+                Instruction::Mul(a, b) => a * b,
+                _ => 0,
+            })
+            .sum())
+    })
 }
 
 #[elvish::solution(day = 3, example = 48)]
-fn part2(input: &str) -> i32 {
-    let set = Regex::new(r"(?<dont>don't)|(?<do>do)|mul\((?<a>\d+),(?<b>\d+)\)").unwrap();
-    let mut enabled = true;
-    set.captures_iter(input)
-        .filter_map(|cap| {
-            cap.name("dont").map(|_| enabled = false);
-            cap.name("do").map(|_| enabled = true);
-
-            if !enabled {
-                return None;
-            }
-
-            let a: i32 = cap.name("a")?.as_str().parse().unwrap();
-            let b: i32 = cap.name("b")?.as_str().parse().unwrap();
-            Some(a * b)
-        })
-        .sum()
+fn part2(input: &str) -> Answer<i32> {
+    Answer::new(|| {
+        let mut enabled = true;
+        Ok(parse(input)?
+            .into_iter()
+            .filter_map(|instruction| {
+                match instruction {
+                    Instruction::Mul(a, b) if enabled => return Some(a * b),
+                    Instruction::Mul(..) => (),
+                    Instruction::Do => enabled = true,
+                    Instruction::Dont => enabled = false,
+                }
+
+                None
+            })
+            .sum())
+    })
 }
 
 elvish::example!(
@@ -1,51 +1,56 @@
-use ndarray::Array2;
+use pathfinding::matrix::directions::DIRECTIONS_8;
 
-fn parse(input: &str) -> Array2<u8> {
-    // TODO: Maybe make a PR to ndarray to add a `from_iter` or even `from_str` and `from_lines` method. 
-    let shape = (input.lines().count(), input.lines().next().unwrap().len());
-    let bytes = input.bytes().filter(|&char| char != b'\n').collect();
-    Array2::from_shape_vec(shape, bytes).unwrap()
+use crate::{answer::Answer, grid::Grid, parse::ParseError};
+
+pub(super) fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(input)
 }
 
 #[elvish::solution(day = 4, example = 18)]
-fn part1(input: &str) -> usize {
-    let grid = parse(input);
-    let directions = || (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)));
+fn part1(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let grid = parse(input)?;
 
-    let grid = &grid;
-    grid.indexed_iter()
-        .flat_map(move |((x, y), _)| {
-            directions().filter(move |&(dx, dy)| {
-                b"XMAS".iter().enumerate().all(|(i, char)| {
-                    grid.get((x + i * dx as usize, y + i * dy as usize)) == Some(&char)
+        let grid = &grid;
+        Ok(grid
+            .positions()
+            .flat_map(move |position| {
+                DIRECTIONS_8.into_iter().filter(move |&(dx, dy)| {
+                    "XMAS".chars().enumerate().all(|(i, char)| {
+                        let i = i as isize;
+                        grid.offset(position, (i * dx, i * dy)).map(|p| grid[p]) == Some(char)
+                    })
                 })
             })
-        })
-        .count()
+            .count())
+    })
 }
 
 #[elvish::solution(day = 4, example = 9)]
-fn part2(input: &str) -> usize {
-    let grid = parse(input);
+fn part2(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let grid = parse(input)?;
 
-    let grid = &grid;
-    grid.indexed_iter()
-        .filter(|&((x, y), char)| {
-            if *char != b'A' {
-                return false;
-            }
+        let grid = &grid;
+        Ok(grid
+            .iter()
+            .filter(|&(position, &char)| {
+                if char != 'A' {
+                    return false;
+                }
 
-            let letters = [(-1i32, -1i32), (-1, 1), (1, 1), (1, -1)]
-                .into_iter()
-                .map(|(dx, dy)| grid.get((x + dx as usize, y + dy as usize)))
-                .collect::<Vec<_>>();
+                let letters = [(-1, -1), (-1, 1), (1, 1), (1, -1)]
+                    .into_iter()
+                    .map(|delta| grid.offset(position, delta).map(|p| grid[p]))
+                    .collect::<Vec<_>>();
 
-            let is_cross = (0..2).any(|i| letters[i] == letters[i + 1]);
-            let two_of = |x| letters.iter().filter(|&&letter| letter == Some(&x)).count() == 2;
+                let is_cross = (0..2).any(|i| letters[i] == letters[i + 1]);
+                let two_of = |x| letters.iter().filter(|&&letter| letter == Some(x)).count() == 2;
 
-            is_cross && two_of(b'M') && two_of(b'S')
-        })
-        .count()
+                is_cross && two_of('M') && two_of('S')
+            })
+            .count())
+    })
 }
 
 elvish::example!(
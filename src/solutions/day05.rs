@@ -1,97 +1,87 @@
+use color_eyre::eyre;
 use std::cmp::Ordering;
+use winnow::combinator::separated_pair;
 
-fn parse(input: &str) -> (Vec<(i32, i32)>, Vec<Vec<i32>>) {
-    let mut iter = input.split("\n\n");
-    let rules = iter.next().unwrap();
-    let prints = iter.next().unwrap();
+use crate::{
+    answer::Answer,
+    parse::{self, uint},
+};
 
-    let rules = rules
-        .lines()
-        .map(|line| {
-            let mut iter = line.split("|");
-            let before = iter.next().unwrap().parse::<i32>().unwrap();
-            let after = iter.next().unwrap().parse::<i32>().unwrap();
+pub(super) fn parse(input: &str) -> eyre::Result<(Vec<(i32, i32)>, Vec<Vec<i32>>)> {
+    let rules = parse::lines(separated_pair(uint, '|', uint));
+    let prints = parse::lines(parse::comma_list(uint));
 
-            (before, after)
-        })
-        .collect::<Vec<_>>();
-
-    let prints = prints
-        .lines()
-        .map(|line| {
-            line.split(",")
-                .map(|s| s.parse::<i32>().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    (rules, prints)
+    Ok(parse::all(input, separated_pair(rules, parse::blank_line, prints))?)
 }
 
 #[elvish::solution(day = 5, example = 143)]
-fn part1(input: &str) -> i32 {
-    let (rules, prints) = parse(input);
+fn part1(input: &str) -> Answer<i32> {
+    Answer::new(|| {
+        let (rules, prints) = parse(input)?;
 
-    let mut correct_prints = vec![true; prints.len()];
-    for (before, after) in rules {
-        for (i, print) in prints.iter().enumerate() {
-            if let (Some(before), Some(after)) = (
-                print.iter().position(|&x| x == before),
-                print.iter().position(|&x| x == after),
-            ) {
-                if before > after {
-                    correct_prints[i] = false;
+        let mut correct_prints = vec![true; prints.len()];
+        for (before, after) in rules {
+            for (i, print) in prints.iter().enumerate() {
+                if let (Some(before), Some(after)) = (
+                    print.iter().position(|&x| x == before),
+                    print.iter().position(|&x| x == after),
+                ) {
+                    if before > after {
+                        correct_prints[i] = false;
+                    }
                 }
             }
         }
-    }
 
-    correct_prints
-        .into_iter()
-        .zip(prints.into_iter())
-        .filter(|&(is_correct, _)| is_correct)
-        .map(|(_, print)| print[print.len() / 2])
-        .sum()
+        Ok(correct_prints
+            .into_iter()
+            .zip(prints.into_iter())
+            .filter(|&(is_correct, _)| is_correct)
+            .map(|(_, print)| print[print.len() / 2])
+            .sum())
+    })
 }
 
 #[elvish::solution(day = 5, example = 123)]
-fn part2(input: &str) -> i32 {
-    let (rules, prints) = parse(input);
+fn part2(input: &str) -> Answer<i32> {
+    Answer::new(|| {
+        let (rules, prints) = parse(input)?;
 
-    let mut correct_prints = vec![true; prints.len()];
-    for &(before, after) in &rules {
-        for (i, print) in prints.iter().enumerate() {
-            if let (Some(before), Some(after)) = (
-                print.iter().position(|&x| x == before),
-                print.iter().position(|&x| x == after),
-            ) {
-                if before > after {
-                    correct_prints[i] = false;
+        let mut correct_prints = vec![true; prints.len()];
+        for &(before, after) in &rules {
+            for (i, print) in prints.iter().enumerate() {
+                if let (Some(before), Some(after)) = (
+                    print.iter().position(|&x| x == before),
+                    print.iter().position(|&x| x == after),
+                ) {
+                    if before > after {
+                        correct_prints[i] = false;
+                    }
                 }
             }
         }
-    }
 
-    correct_prints
-        .into_iter()
-        .zip(prints.into_iter())
-        .filter(|&(is_correct, _)| !is_correct)
-        .map(|(_, mut print)| {
-            print.sort_by(|&a, &b| {
-                for &(before, after) in &rules {
-                    if a == before && b == after {
-                        return Ordering::Less;
-                    } else if a == after && b == before {
-                        return Ordering::Greater;
+        Ok(correct_prints
+            .into_iter()
+            .zip(prints.into_iter())
+            .filter(|&(is_correct, _)| !is_correct)
+            .map(|(_, mut print)| {
+                print.sort_by(|&a, &b| {
+                    for &(before, after) in &rules {
+                        if a == before && b == after {
+                            return Ordering::Less;
+                        } else if a == after && b == before {
+                            return Ordering::Greater;
+                        }
                     }
-                }
 
-                Ordering::Equal
-            });
+                    Ordering::Equal
+                });
 
-            print[print.len() / 2]
-        })
-        .sum()
+                print[print.len() / 2]
+            })
+            .sum())
+    })
 }
 
 elvish::example!(
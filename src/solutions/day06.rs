@@ -1,108 +1,78 @@
-use rayon::prelude::*;
+use color_eyre::eyre;
 use indexmap::IndexSet;
-use nalgebra::{vector, Vector2};
-
-#[elvish::solution(day = 6, example = 41)]
-fn part1(input: &str) -> usize {
-    let mut position = Vector2::zeros();
-    let mut obstacles = IndexSet::new();
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, char) in line.chars().enumerate() {
-            let coords = vector![x as i32, y as i32];
-
-            match char {
-                '#' => {
-                    obstacles.insert(coords);
-                }
-                '^' => position = coords,
-                _ => (),
-            }
-        }
-    }
+use rayon::prelude::*;
 
-    let bounds = vector![
-        input.lines().next().unwrap().len() as i32,
-        input.lines().count() as i32
-    ];
+use crate::{
+    answer::Answer,
+    grid::Grid,
+    vec2::{Direction, Vec2, Vec2Ext as _},
+};
 
-    let mut velocity = vector![0, -1];
-    let mut hit = IndexSet::new();
-    while (position + velocity)
-        .zip_map(&bounds, |p, b| p < b && p >= 0)
-        .iter()
-        .all(|&x| x)
-    {
-        // while position.x < bounds.x && position.y < bounds.y {
-        if obstacles.contains(&(position + velocity)) {
-            velocity = vector![-velocity.y, velocity.x];
-        } else {
-            position += velocity;
-            hit.insert(position);
-        }
-    }
+/// The map, where the guard starts, and the obstacles.
+fn parse(input: &str) -> eyre::Result<(Grid<char>, Vec2, IndexSet<Vec2>)> {
+    let grid = Grid::parse_with(input, |char| "#.^".contains(char).then_some(char))?;
+    let start = Vec2::from_position(grid.find(&'^')?);
+    let obstacles = grid.find_all(&'#').map(Vec2::from_position).collect();
 
-    hit.len()
+    Ok((grid, start, obstacles))
 }
 
-#[elvish::solution(day = 6, example = 6)]
-fn part2(input: &str) -> usize {
-    let mut initial_position = Vector2::zeros();
-    let mut obstacles = IndexSet::new();
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, char) in line.chars().enumerate() {
-            let coords = vector![x as i32, y as i32];
-
-            match char {
-                '#' => {
-                    obstacles.insert(coords);
-                }
-                '^' => initial_position = coords,
-                _ => (),
+#[elvish::solution(day = 6, example = 41)]
+fn part1(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let (grid, mut position, obstacles) = parse(input)?;
+
+        let mut direction = Direction::Up;
+        let mut hit = IndexSet::new();
+        while grid.at(position + direction).is_some() {
+            if obstacles.contains(&(position + direction)) {
+                direction = direction.turn_right();
+            } else {
+                position += direction;
+                hit.insert(position);
             }
         }
-    }
-
-    let bounds = vector![
-        input.lines().next().unwrap().len() as i32,
-        input.lines().count() as i32
-    ];
-
-    let mut count = 0;
-
-    for x in 0..bounds.x {
-        count += (0..bounds.y).into_par_iter().filter(|&y| {
-            let coords = vector![x, y];
-            let mut new_obstacles = obstacles.clone();
-            new_obstacles.insert(coords);
 
-            let mut velocity = vector![0, -1];
-            let mut visited = IndexSet::new();
-            let mut position = initial_position.clone();
-
-            while (position + velocity)
-                .zip_map(&bounds, |p, b| p < b && p >= 0)
-                .iter()
-                .all(|&x| x)
-            {
-                if visited.contains(&(position, velocity)) {
-                    return true;
-                }
+        Ok(hit.len())
+    })
+}
 
-                if new_obstacles.contains(&(position + velocity)) {
-                    velocity = vector![-velocity.y, velocity.x];
-                } else {
-                    visited.insert((position, velocity));
-                    position += velocity;
+#[elvish::solution(day = 6, example = 6)]
+fn part2(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let (grid, initial_position, obstacles) = parse(input)?;
+
+        let mut count = 0;
+
+        for x in 0..grid.width() {
+            count += (0..grid.height()).into_par_iter().filter(|&y| {
+                let coords = Vec2::from_position((x, y));
+                let mut new_obstacles = obstacles.clone();
+                new_obstacles.insert(coords);
+
+                let mut direction = Direction::Up;
+                let mut visited = IndexSet::new();
+                let mut position = initial_position;
+
+                while grid.at(position + direction).is_some() {
+                    if visited.contains(&(position, direction)) {
+                        return true;
+                    }
+
+                    if new_obstacles.contains(&(position + direction)) {
+                        direction = direction.turn_right();
+                    } else {
+                        visited.insert((position, direction));
+                        position += direction;
+                    }
                 }
-            }
 
-            false
-        }).count()
-    }
+                false
+            }).count()
+        }
 
-    count
+        Ok(count)
+    })
 }
 
 elvish::example!(
@@ -1,30 +1,23 @@
-use nom::{bytes::complete::tag, multi::separated_list1, sequence::separated_pair, Parser as _};
+use color_eyre::eyre;
 use pathfinding::prelude::dfs;
 use rayon::prelude::*;
 use std::ops::{Add, Mul};
 
-use crate::parse::digit;
+use crate::{
+    answer::Answer,
+    parse::{self, uint, ParseError},
+};
 
-fn parse_line(line: &str) -> (u64, Vec<u64>) {
-    let (rest, (result, operands)) = separated_pair(
-        digit::<u64>,
-        tag(": "),
-        separated_list1(tag(" "), digit::<u64>),
-    )
-    .parse(line)
-    .unwrap();
-
-    assert!(rest.is_empty());
-
-    (result, operands)
+fn parse(input: &str) -> Result<Vec<(u64, Vec<u64>)>, ParseError> {
+    parse::all(input, parse::lines(parse::key_value(uint, parse::space_list(uint))))
 }
 
-fn solve(input: &str, operators: Vec<impl Fn(u64, u64) -> u64 + Sync>) -> u64 {
-    input
-        .par_lines()
-        .filter_map(|line| {
-            let (result, operands) = parse_line(line);
+fn solve(input: &str, operators: Vec<impl Fn(u64, u64) -> u64 + Sync>) -> eyre::Result<u64> {
+    let equations = parse(input)?;
 
+    Ok(equations
+        .into_par_iter()
+        .filter_map(|(result, operands)| {
             let path = dfs(
                 (operands[0], 0),
                 |&(current, i)| {
@@ -39,12 +32,12 @@ fn solve(input: &str, operators: Vec<impl Fn(u64, u64) -> u64 + Sync>) -> u64 {
 
             path.map(|_| result)
         })
-        .sum()
+        .sum())
 }
 
 #[elvish::solution(day = 7, example = 3749)]
-fn part1(input: &str) -> u64 {
-    solve(input, vec![Add::add, Mul::mul])
+fn part1(input: &str) -> Answer<u64> {
+    solve(input, vec![Add::add, Mul::mul]).into()
 }
 
 /// Concatenates, base 10: `132 || 456 = 132456`
@@ -57,8 +50,8 @@ fn concatenate(a: u64, b: u64) -> u64 {
 }
 
 #[elvish::solution(day = 7, example = 11387)]
-fn part2(input: &str) -> u64 {
-    solve(input, vec![Add::add, Mul::mul, concatenate])
+fn part2(input: &str) -> Answer<u64> {
+    solve(input, vec![Add::add, Mul::mul, concatenate]).into()
 }
 
 elvish::example!(
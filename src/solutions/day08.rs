@@ -1,8 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
-use nalgebra::{vector, Vector2};
+use crate::vec2::Vec2;
 
-fn parse(input: &str) -> HashMap<u8, Vec<Vector2<i32>>> {
+pub(super) fn parse(input: &str) -> HashMap<u8, Vec<Vec2>> {
     let mut stations = HashMap::new();
     for (y, line) in input.lines().enumerate() {
         for (x, char) in line.bytes().enumerate() {
@@ -11,7 +11,7 @@ fn parse(input: &str) -> HashMap<u8, Vec<Vector2<i32>>> {
             }
 
             let entry = stations.entry(char).or_insert(Vec::new());
-            entry.push(vector![x as i32, y as i32])
+            entry.push(Vec2::new(x as i64, y as i64))
         }
     }
 
@@ -20,7 +20,10 @@ fn parse(input: &str) -> HashMap<u8, Vec<Vector2<i32>>> {
 
 #[elvish::solution(day = 8, example = 14)]
 fn part1(input: &str) -> usize {
-    let bounds = vector![input.lines().next().unwrap().len(), input.lines().count(),];
+    let bounds = Vec2::new(
+        input.lines().next().map_or(0, str::len) as i64,
+        input.lines().count() as i64,
+    );
 
     let stations = parse(input);
 
@@ -39,7 +42,7 @@ fn part1(input: &str) -> usize {
         .into_iter()
         .filter(|&antinode| {
             antinode
-                .zip_map(&bounds, |a, b| a >= 0 && a < b as i32)
+                .zip_map(&bounds, |a, b| a >= 0 && a < b)
                 .iter()
                 .all(|&x| x)
         })
@@ -48,7 +51,10 @@ fn part1(input: &str) -> usize {
 
 #[elvish::solution(day = 8, example = 34)]
 fn part2(input: &str) -> usize {
-    let bounds = vector![input.lines().next().unwrap().len(), input.lines().count(),];
+    let bounds = Vec2::new(
+        input.lines().next().map_or(0, str::len) as i64,
+        input.lines().count() as i64,
+    );
 
     let stations = parse(input);
 
@@ -57,14 +63,14 @@ fn part2(input: &str) -> usize {
     for stations in stations.values() {
         for &a in stations {
             for &b in stations {
-                dbg!(a, b);
                 if a == b {
                     continue;
                 }
 
                 for i in 0.. {
                     let antinode = i * a - (i - 1) * b;
-                    if antinode.x >= bounds.x as i32 || antinode.y >= bounds.y as i32 {
+                    if !(0..bounds.x).contains(&antinode.x) || !(0..bounds.y).contains(&antinode.y)
+                    {
                         break;
                     }
                     visited.insert(antinode);
@@ -1,90 +1,83 @@
 use std::collections::HashSet;
 
-use ndarray::Array2;
-use pathfinding::matrix::directions::DIRECTIONS_4;
+use crate::{
+    answer::Answer,
+    grid::{Grid, Position},
+    parse::ParseError,
+};
 
-fn parse(input: &str) -> Array2<u8> {
-    let shape = (input.lines().count(), input.lines().next().unwrap().len());
-    let bytes = input
-        .bytes()
-        .filter(|&char| char != b'\n')
-        .map(|char| char - b'0')
-        .collect();
-    Array2::from_shape_vec(shape, bytes).unwrap()
+pub(super) fn parse(input: &str) -> Result<Grid<u8>, ParseError> {
+    Grid::parse_with(input, |char| char.to_digit(10).map(|height| height as u8))
 }
 
 #[elvish::solution(day = 10, example = 36)]
-fn part1(input: &str) -> u32 {
-    let grid = parse(input);
-    let trailheads = grid
-        .indexed_iter()
-        .filter(|(_, &h)| h == 0)
-        .map(|(pos, _)| pos);
+fn part1(input: &str) -> Answer<u32> {
+    Answer::new(|| {
+        let grid = parse(input)?;
+        let trailheads = grid.find_all(&0);
 
-    trailheads
-        .map(|trailhead| {
-            let mut visited = HashSet::with_capacity(grid.len());
-            let mut queue = vec![trailhead];
-            let mut score = 0;
+        Ok(trailheads
+            .map(|trailhead| {
+                let mut visited = HashSet::with_capacity(grid.width() * grid.height());
+                let mut queue = vec![trailhead];
+                let mut score = 0;
 
-            while let Some((x, y)) = queue.pop() {
-                if !visited.insert((x, y)) {
-                    continue;
-                }
+                while let Some(position) = queue.pop() {
+                    if !visited.insert(position) {
+                        continue;
+                    }
 
-                let h = grid[(x, y)];
-                if h == 9 {
-                    score += 1;
-                }
+                    let h = grid[position];
+                    if h == 9 {
+                        score += 1;
+                    }
 
-                for (dx, dy) in DIRECTIONS_4 {
-                    let next_pos = (x.wrapping_add(dx as usize), y.wrapping_add(dy as usize));
-                    if let Some(&new_height) = grid.get(next_pos) {
-                        if new_height == h + 1 {
+                    for next_pos in grid.neighbors4(position) {
+                        if grid[next_pos] == h + 1 {
                             queue.push(next_pos)
                         }
                     }
                 }
-            }
 
-            score
-        })
-        .sum()
+                score
+            })
+            .sum())
+    })
 }
 
 #[elvish::solution(day = 10, example = 81)]
-fn part2(input: &str) -> u32 {
-    let grid = parse(input);
-    let trailheads = grid
-        .indexed_iter()
-        .filter(|(_, &h)| h == 0)
-        .map(|(pos, _)| pos);
+fn part2(input: &str) -> Answer<u32> {
+    Answer::new(|| {
+        let grid = parse(input)?;
+        let trailheads = grid.find_all(&0);
 
-    trailheads
-        .map(|trailhead| {
-            fn explore((x, y): (usize, usize), grid: &Array2<u8>, visited: &mut HashSet<(usize, usize)>) -> u32 {
-                let mut rating = 0;
-                let h = grid[(x, y)];
-                if h == 9 {
-                    rating += 1;
-                }
+        Ok(trailheads
+            .map(|trailhead| {
+                fn explore(
+                    position: Position,
+                    grid: &Grid<u8>,
+                    visited: &mut HashSet<Position>,
+                ) -> u32 {
+                    let mut rating = 0;
+                    let h = grid[position];
+                    if h == 9 {
+                        rating += 1;
+                    }
 
-                for (dx, dy) in DIRECTIONS_4 {
-                    let next_pos = (x.wrapping_add(dx as usize), y.wrapping_add(dy as usize));
-                    if let Some(&new_height) = grid.get(next_pos) {
-                        if new_height == h + 1 {
+                    for next_pos in grid.neighbors4(position) {
+                        if grid[next_pos] == h + 1 {
                             rating += explore(next_pos, grid, visited);
                         }
                     }
-                }
 
-                return rating
-            }
+                    return rating
+                }
 
-            let mut visited = HashSet::new();
-            explore(trailhead, &grid, &mut visited)
-        })
-        .sum()
+                let mut visited = HashSet::new();
+                explore(trailhead, &grid, &mut visited)
+            })
+            .sum())
+    })
 }
 
 elvish::example!(
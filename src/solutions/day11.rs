@@ -1,10 +1,10 @@
+use color_eyre::eyre;
 use std::collections::HashMap;
 
+use crate::{answer::Answer, parse};
 
-fn solve(input: &str, total_blinks: u32) -> u64 {
-    let stones = input
-        .split_ascii_whitespace()
-        .map(|num| num.parse::<u64>().unwrap());
+fn solve(input: &str, total_blinks: u32) -> eyre::Result<u64> {
+    let stones = parse::all(input, parse::space_list(parse::uint::<u64>))?;
 
     fn explore(stone: u64, blink: u32, total_blinks: u32, cache: &mut HashMap<(u64, u32), u64>) -> u64 {
         if let Some(output) = cache.get(&(stone, blink)) {
@@ -41,17 +41,17 @@ fn solve(input: &str, total_blinks: u32) -> u64 {
 
     let mut cache = HashMap::new();
 
-    stones.map(|stone| explore(stone, 0, total_blinks, &mut cache)).sum()
+    Ok(stones.into_iter().map(|stone| explore(stone, 0, total_blinks, &mut cache)).sum())
 }
 
 #[elvish::solution(day = 11, example = 55312)]
-fn part1(input: &str) -> u64 {
-    solve(input, 25)
+fn part1(input: &str) -> Answer<u64> {
+    solve(input, 25).into()
 }
 
 #[elvish::solution(day = 11, example = 65601038650482)]
-fn part2(input: &str) -> u64 {
-    solve(input, 75)
+fn part2(input: &str) -> Answer<u64> {
+    solve(input, 75).into()
 }
 
 elvish::example!("125 17");
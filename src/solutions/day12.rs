@@ -1,122 +1,121 @@
 use std::collections::{BTreeSet, HashSet, VecDeque};
 
-use ndarray::Array2;
-use pathfinding::matrix::directions::DIRECTIONS_4;
-
-fn parse(input: &str) -> Array2<u8> {
-    let shape = (input.lines().count(), input.lines().next().unwrap().len());
-    let bytes = input.bytes().filter(|&char| char != b'\n').collect();
-
-    Array2::from_shape_vec(shape, bytes).unwrap()
+use crate::{
+    answer::Answer,
+    grid::{Grid, Position},
+    parse::ParseError,
+    vec2::Direction,
+};
+
+pub(super) fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(input)
 }
 
 #[elvish::solution(day = 12, example = 1930)]
-fn part1(input: &str) -> u32 {
-    let grid = parse(input);
+fn part1(input: &str) -> Answer<u32> {
+    Answer::new(|| {
+        let grid = parse(input)?;
 
-    let mut visited = HashSet::new();
+        let mut visited = HashSet::new();
 
-    let explore_region = |pos: (usize, usize), char, visited: &mut HashSet<(usize, usize)>| {
-        let mut perimeter = 0;
-        let mut area = 0;
+        let explore_region = |pos: Position, char, visited: &mut HashSet<Position>| {
+            let mut perimeter = 0;
+            let mut area = 0;
 
-        let mut queue = vec![pos];
-        while let Some(pos) = queue.pop() {
-            if !visited.insert(pos) {
-                continue;
-            }
+            let mut queue = vec![pos];
+            while let Some(pos) = queue.pop() {
+                if !visited.insert(pos) {
+                    continue;
+                }
 
-            area += 1;
+                area += 1;
 
-            for (neighbor, _) in neighbors(pos) {
-                if grid.get(neighbor) == Some(&char) {
-                    queue.push(neighbor)
-                } else {
-                    perimeter += 1;
+                for direction in Direction::ALL {
+                    match grid.step(pos, direction) {
+                        Some(neighbor) if grid[neighbor] == char => queue.push(neighbor),
+                        _ => perimeter += 1,
+                    }
                 }
             }
-        }
-
-        (perimeter, area)
-    };
-
-    grid.indexed_iter()
-        .map(|(pos, &char)| {
-            if visited.contains(&pos) {
-                0
-            } else {
-                let (perimeter, area) = explore_region(pos, char, &mut visited);
-                perimeter * area
-            }
-        })
-        .sum()
-}
 
-fn neighbors((x, y): (usize, usize)) -> impl Iterator<Item = ((usize, usize), (isize, isize))> {
-    DIRECTIONS_4.into_iter().map(move |(dx, dy)| {
-        let neighbor = (x.wrapping_add(dx as usize), y.wrapping_add(dy as usize));
-        (neighbor, (dx, dy))
+            (perimeter, area)
+        };
+
+        Ok(grid
+            .iter()
+            .map(|(pos, &char)| {
+                if visited.contains(&pos) {
+                    0
+                } else {
+                    let (perimeter, area) = explore_region(pos, char, &mut visited);
+                    perimeter * area
+                }
+            })
+            .sum())
     })
 }
 
 #[elvish::solution(day = 12, example = 1206)]
-fn part2(input: &str) -> u32 {
-    let grid = parse(input);
+fn part2(input: &str) -> Answer<u32> {
+    Answer::new(|| {
+        let grid = parse(input)?;
 
-    let mut visited = HashSet::new();
+        let mut visited = HashSet::new();
 
-    let explore_region = |pos: (usize, usize), char, visited: &mut HashSet<(usize, usize)>| {
-        let mut queue = VecDeque::from([pos]);
+        let explore_region = |pos: Position, char, visited: &mut HashSet<Position>| {
+            let mut queue = VecDeque::from([pos]);
 
-        let mut perimeter = BTreeSet::new();
-        let mut area = 0;
+            // The tiles of the region on the edge, and which way the edge faces.
+            let mut perimeter = BTreeSet::new();
+            let mut area = 0;
 
-        while let Some(pos) = queue.pop_front() {
-            if !visited.insert(pos) {
-                continue;
-            }
+            while let Some(pos) = queue.pop_front() {
+                if !visited.insert(pos) {
+                    continue;
+                }
 
-            area += 1;
+                area += 1;
 
-            for (neighbor, delta) in neighbors(pos) {
-                if grid.get(neighbor) == Some(&char) {
-                    queue.push_back(neighbor)
-                } else {
-                    perimeter.insert((neighbor, delta));
+                for direction in Direction::ALL {
+                    match grid.step(pos, direction) {
+                        Some(neighbor) if grid[neighbor] == char => queue.push_back(neighbor),
+                        _ => {
+                            perimeter.insert((pos, direction));
+                        }
+                    }
                 }
             }
-        }
 
-        let mut sides = 0;
-        while let Some(&(pos, delta)) = perimeter.first() {
-            sides += 1;
-            let mut queue = vec![pos];
+            let mut sides = 0;
+            while let Some(&(pos, direction)) = perimeter.first() {
+                sides += 1;
+                let mut queue = vec![pos];
 
-            // Delete all contiguous edge from perimeter (to not double count)
-            while let Some(pos) = queue.pop() {
-                if !perimeter.remove(&(pos, delta)) {
-                    continue;
-                }
+                // Delete all contiguous edge from perimeter (to not double count)
+                while let Some(pos) = queue.pop() {
+                    if !perimeter.remove(&(pos, direction)) {
+                        continue;
+                    }
 
-                for (nn, _) in neighbors(pos) {
-                    queue.push(nn);
+                    queue.extend(grid.neighbors4(pos));
                 }
             }
-        }
-
-        (sides, area)
-    };
-
-    grid.indexed_iter()
-        .map(|(pos, &char)| {
-            if visited.contains(&pos) {
-                0
-            } else {
-                let (sides, area) = explore_region(pos, char, &mut visited);
-                sides * area
-            }
-        })
-        .sum()
+
+            (sides, area)
+        };
+
+        Ok(grid
+            .iter()
+            .map(|(pos, &char)| {
+                if visited.contains(&pos) {
+                    0
+                } else {
+                    let (sides, area) = explore_region(pos, char, &mut visited);
+                    sides * area
+                }
+            })
+            .sum())
+    })
 }
 
 elvish::example!(
@@ -1,69 +1,256 @@
 use color_eyre::eyre;
-use nalgebra::Matrix2;
-use nom::{branch::alt, sequence::{separated_pair, tuple}, IResult, Parser as _};
-use nom_supreme::{tag::complete::tag, ParserExt as _};
-use std::str::FromStr;
+use winnow::{
+    ascii::line_ending,
+    combinator::{preceded, repeat, separated_pair, seq, terminated},
+    error::StrContext,
+    token::one_of,
+    PResult, Parser as _,
+};
 
-use crate::parse;
+use crate::{
+    answer::Answer,
+    parse::{self, uint},
+};
 
 type Vec2<T = u64> = nalgebra::Vector2<T>;
 
+/// How many tokens pressing each button costs: 3 for A and 1 for B.
+const COSTS: [u64; 2] = [3, 1];
+
 struct Machine {
-    buttons: [Vec2; 2],
+    buttons: Vec<Vec2>,
     prize: Vec2,
 }
 
-
 impl Machine {
-    pub fn a(&self) -> Vec2 {
-        self.buttons[0]
+    /// The fewest tokens to get to the prize, pressing the `i`th button for
+    /// `costs[i]` tokens each time.
+    pub fn min_tokens(&self, costs: &[u64]) -> eyre::Result<Option<u64>> {
+        eyre::ensure!(
+            costs.len() == self.buttons.len(),
+            "Expected a cost for each of the {} buttons, got {}",
+            self.buttons.len(),
+            costs.len()
+        );
+
+        let buttons = self.buttons.iter().map(|button| button.map(i128::from)).collect::<Vec<_>>();
+        let costs = costs.iter().copied().map(i128::from).collect::<Vec<_>>();
+        let tokens = min_cost(&buttons, &costs, self.prize.map(i128::from))?;
+
+        Ok(tokens.map(|tokens| tokens.try_into()).transpose()?)
     }
+}
+
+/// How many presses of a button get tried one by one, for machines that can't
+/// be solved exactly. Past that it's an error instead of running for ages.
+const MAX_PRESSES: i128 = 1_000_000;
+
+fn min_cost(
+    buttons: &[Vec2<i128>],
+    costs: &[i128],
+    prize: Vec2<i128>,
+) -> eyre::Result<Option<i128>> {
+    match *buttons {
+        [] => Ok((prize == Vec2::zeros()).then_some(0)),
+        [a] => Ok(pair([a, Vec2::zeros()], [costs[0], 0], prize)),
+        [a, b] => Ok(pair([a, b], [costs[0], costs[1]], prize)),
+        [a, b, c] if cross(a, b) != 0 => {
+            Ok(triple([a, b, c], [costs[0], costs[1], costs[2]], prize))
+        }
 
-    pub fn b(&self) -> Vec2 {
-        self.buttons[1]
+        // Any more buttons get tried with every number of presses that fits.
+        [ref rest @ .., last] => {
+            let (rest_costs, &[cost]) = costs.split_at(rest.len()) else {
+                unreachable!()
+            };
+
+            let most = most_presses(last, prize);
+            eyre::ensure!(
+                most <= MAX_PRESSES,
+                "Button {last:?} fits up to {most} times, too many to try them all"
+            );
+
+            let mut cheapest = None;
+            for presses in 0..=most {
+                if let Some(rest_cost) = min_cost(rest, rest_costs, prize - last * presses)? {
+                    let total = rest_cost + presses * cost;
+                    cheapest = Some(cheapest.map_or(total, |cheapest: i128| cheapest.min(total)));
+                }
+            }
+
+            Ok(cheapest)
+        }
     }
+}
+
+fn cross(u: Vec2<i128>, v: Vec2<i128>) -> i128 {
+    u.x * v.y - u.y * v.x
+}
+
+/// How many times `button` can be pressed before it overshoots the prize.
+fn most_presses(button: Vec2<i128>, prize: Vec2<i128>) -> i128 {
+    (0..2)
+        .filter(|&axis| button[axis] != 0)
+        .map(|axis| prize[axis] / button[axis])
+        .min()
+        .unwrap_or(0)
+}
+
+/// Like [`pair`], with a third button `c` pressed some number of times.
+///
+/// `a` and `b` only make up the rest of the way when the presses of `c` are in
+/// a certain residue class, modulo the period of their lattice (`det / gcd`).
+/// Along that class the presses of `a` and `b` (and so the cost) change
+/// linearly, so only the ends of the range where they're all positive need
+/// checking.
+fn triple([a, b, c]: [Vec2<i128>; 3], costs: [i128; 3], prize: Vec2<i128>) -> Option<i128> {
+    let gcd = |u, v| extended_gcd(u, v).0;
+    let determinant = cross(a, b);
 
-    pub fn min_tokens(&self) -> Option<u64> {
-        // Idea: we have to solve a system of equations.
-        // Ax * x + Bx * y = Px
-        // Ay * x + By * y = Py
+    // With `p` presses of `c`, `a` is pressed `(a0 - p * a1) / determinant`
+    // times, and the same for `b`.
+    let (a0, a1) = (cross(prize, b), cross(c, b));
+    let (b0, b1) = (cross(a, prize), cross(a, c));
 
-        let matrix = Matrix2::from_columns(&[self.a(), self.b()]);
-        let result = self.prize.cast();
+    let (period_a, period_b) = (
+        determinant / gcd(determinant, a1),
+        determinant / gcd(determinant, b1),
+    );
+    let period = (period_a / gcd(period_a, period_b) * period_b).abs();
+    let first = (0..period)
+        .find(|p| (a0 - p * a1) % determinant == 0 && (b0 - p * b1) % determinant == 0)?;
 
-        let clicks = matrix.cast::<f64>().try_inverse().unwrap() * result;
-        let clicks = clicks.map(|x| x.round()).try_cast().unwrap();
+    // Every `p = first + period * t`, where `a` is pressed `na0 - na1 * t`
+    // times and `b` `nb0 - nb1 * t` times.
+    let [(na0, na1), (nb0, nb1)] = [(a0, a1), (b0, b1)]
+        .map(|(n0, n1)| ((n0 - first * n1) / determinant, period * n1 / determinant));
 
-        // Check integer result
-        let hyp_result = matrix.cast::<u64>() * clicks;
-        if hyp_result != self.prize.cast() {
+    let (mut low, mut high) = (0, (most_presses(c, prize) - first).div_euclid(period));
+    for (n0, n1) in [(na0, na1), (nb0, nb1)] {
+        match n1.signum() {
+            1 => high = high.min(n0.div_euclid(n1)),
+            -1 => low = low.max(-n0.div_euclid(-n1)),
+            _ if n0 < 0 => return None,
+            _ => (),
+        }
+    }
+
+    (low <= high).then(|| {
+        [low, high]
+            .into_iter()
+            .map(|t| {
+                (na0 - na1 * t) * costs[0]
+                    + (nb0 - nb1 * t) * costs[1]
+                    + (first + period * t) * costs[2]
+            })
+            .min()
+            .unwrap()
+    })
+}
+
+/// Exactly solves
+///
+/// ```text
+/// Ax * a + Bx * b = Px
+/// Ay * a + By * b = Py
+/// ```
+///
+/// with Cramer's rule, or along the line the buttons share if they point the
+/// same way.
+fn pair([a, b]: [Vec2<i128>; 2], costs: [i128; 2], prize: Vec2<i128>) -> Option<i128> {
+    let determinant = cross(a, b);
+    if determinant != 0 {
+        let presses_a = cross(prize, b);
+        let presses_b = cross(a, prize);
+        if presses_a % determinant != 0 || presses_b % determinant != 0 {
             return None;
         }
 
-        Some(clicks.x * 3 + clicks.y)
+        let (presses_a, presses_b) = (presses_a / determinant, presses_b / determinant);
+        return (presses_a >= 0 && presses_b >= 0)
+            .then_some(presses_a * costs[0] + presses_b * costs[1]);
+    }
+
+    // The buttons are collinear, so the prize has to be on their line too.
+    let Some(direction) = [a, b].into_iter().find(|button| *button != Vec2::zeros()) else {
+        return (prize == Vec2::zeros()).then_some(0);
+    };
+    if cross(direction, prize) != 0 {
+        return None;
     }
+
+    // Then only one axis matters.
+    let axis = if direction.x != 0 { 0 } else { 1 };
+    line([a[axis], b[axis]], costs, prize[axis])
 }
 
-// Too low: 25173
+/// The cheapest `a * u + b * v = w` with `a, b >= 0`, out of all the solutions
+/// from the extended Euclidean algorithm.
+fn line([u, v]: [i128; 2], [cost_u, cost_v]: [i128; 2], w: i128) -> Option<i128> {
+    match (u, v) {
+        (0, 0) => return (w == 0).then_some(0),
+        (0, v) => return (w % v == 0 && w / v >= 0).then_some(w / v * cost_v),
+        (u, 0) => return (w % u == 0 && w / u >= 0).then_some(w / u * cost_u),
+        _ => (),
+    }
 
-#[elvish::solution(day = 13, example = 480)]
-fn part1(input: &str) -> u64 {
-    let machines = input.split("\n\n").map(|s| s.parse::<Machine>().unwrap());
+    let (gcd, x, y) = extended_gcd(u, v);
+    if w % gcd != 0 {
+        return None;
+    }
 
-    machines.filter_map(|m| m.min_tokens()).sum()
+    // Every solution is `a = a0 + step_a * t` and `b = b0 - step_b * t`.
+    let (a0, b0) = (x * (w / gcd), y * (w / gcd));
+    let (step_a, step_b) = (v / gcd, u / gcd);
+
+    let first = -(a0.div_euclid(step_a));
+    let last = b0.div_euclid(step_b);
+    let first = if (a0 + step_a * first) < 0 { first + 1 } else { first };
+    if first > last {
+        return None;
+    }
+
+    // The cost is linear in `t`, so the cheapest is at one of the ends.
+    [first, last]
+        .into_iter()
+        .map(|t| (a0 + step_a * t) * cost_u + (b0 - step_b * t) * cost_v)
+        .min()
+}
+
+/// `(gcd, x, y)` such that `a * x + b * y = gcd`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a.abs(), a.signum(), 0)
+    } else {
+        let (gcd, x, y) = extended_gcd(b, a % b);
+        (gcd, y, x - (a / b) * y)
+    }
+}
+
+fn total_tokens(machines: impl IntoIterator<Item = Machine>) -> eyre::Result<u64> {
+    let mut total = 0;
+    for machine in machines {
+        total += machine.min_tokens(&COSTS)?.unwrap_or(0);
+    }
+
+    Ok(total)
+}
+
+#[elvish::solution(day = 13, example = 480)]
+fn part1(input: &str) -> Answer<u64> {
+    Answer::new(|| total_tokens(parse(input)?))
 }
 
 #[elvish::solution(day = 13, example = 281)]
-fn part2(input: &str) -> u64 {
-    let machines = input
-        .split("\n\n")
-        .map(|s| s.parse::<Machine>().unwrap())
-        .map(|mut machine| {
+fn part2(input: &str) -> Answer<u64> {
+    Answer::new(|| {
+        let machines = parse(input)?.into_iter().map(|mut machine| {
             machine.prize += Vec2::new(1, 1) * 10000000000000;
             machine
         });
 
-    machines.filter_map(|m| m.min_tokens()).sum()
+        total_tokens(machines)
+    })
 }
 
 elvish::example!(
@@ -90,40 +277,117 @@ elvish::example!(
 );
 
 
-impl FromStr for Machine {
-    type Err = eyre::Error;
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        // Ugly
-        fn coord(axis: &str) -> impl Fn(&str) -> IResult<&str, u64> + use<'_> {
-            move |input| {
-                tuple((tag(axis), alt((tag("+"), tag("="))), parse::digit::<u64>))
-                    .map(|(_, _, digit)| digit)
-                    .parse(input)
-            }
-        }
+fn parse(input: &str) -> eyre::Result<Vec<Machine>> {
+    /// Like `X+94, Y+34` or `X=8400, Y=5400`.
+    fn vec(input: &mut &str) -> PResult<Vec2> {
+        let coord = |axis| preceded((axis, one_of(['+', '='])), uint::<u64>);
+        separated_pair(coord('X'), ", ", coord('Y'))
+            .map(|(x, y)| Vec2::new(x, y))
+            .parse_next(input)
+    }
 
-        fn vec<'a>(input: &'a str) -> IResult<&'a str, Vec2> {
-            separated_pair(coord("X"), tag(", "), coord("Y"))
-                .map(|(x, y)| Vec2::new(x, y))
-                .parse(input)
-        }
+    /// Like `Button A: X+94, Y+34`, for any letter.
+    fn button(input: &mut &str) -> PResult<Vec2> {
+        preceded(("Button ", one_of('A'..='Z'), ": "), vec)
+            .context(StrContext::Label("Button"))
+            .parse_next(input)
+    }
+
+    let machine = seq!(
+        repeat(1.., terminated(button, line_ending)),
+        parse::field("Prize", vec),
+    )
+    .map(|(buttons, prize)| Machine { buttons, prize });
+
+    Ok(parse::all(input, parse::blocks(machine))?)
+}
+
+#[test]
+fn exact_tokens() {
+    let machine = |buttons: &[(u64, u64)], prize: (u64, u64)| Machine {
+        buttons: buttons.iter().map(|&(x, y)| Vec2::new(x, y)).collect(),
+        prize: Vec2::new(prize.0, prize.1),
+    };
 
-        let (rest, a) = vec
-            .preceded_by(tag("Button A: "))
-            .parse(input.trim())
-            .map_err(|e| e.to_owned())?;
-        let (rest, b) = vec
-            .preceded_by(tag("Button B: "))
-            .parse(rest.trim())
-            .map_err(|e| e.to_owned())?;
-        let (_, prize) = vec
-            .preceded_by(tag("Prize: "))
-            .parse(rest.trim())
-            .map_err(|e| e.to_owned())?;
-
-        Ok(Machine {
-            buttons: [a, b],
-            prize,
-        })
+    let machines = parse(EXAMPLE_PART1).unwrap();
+    let tokens = machines
+        .iter()
+        .map(|machine| machine.min_tokens(&COSTS).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(tokens, [Some(280), None, Some(200), None]);
+
+    // Far enough that an `f64` would lose the last digits.
+    let far = machine(&[(26, 66), (67, 21)], (10000000012748, 10000000012176));
+    assert_eq!(far.min_tokens(&COSTS).unwrap(), Some(459236326669));
+
+    // Collinear buttons: 1 A and 2 Bs is cheaper than 5 As, and 3 Bs don't fit.
+    let collinear = machine(&[(3, 3), (2, 2)], (7, 7));
+    assert_eq!(collinear.min_tokens(&COSTS).unwrap(), Some(5));
+    assert_eq!(
+        machine(&[(3, 3), (2, 2)], (7, 8))
+            .min_tokens(&COSTS)
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        machine(&[(4, 4), (2, 2)], (7, 7))
+            .min_tokens(&COSTS)
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        machine(&[(1, 2), (0, 0)], (3, 6))
+            .min_tokens(&COSTS)
+            .unwrap(),
+        Some(9)
+    );
+
+    // A third button, and any costs.
+    let three = machine(&[(1, 0), (0, 1), (1, 1)], (3, 5));
+    assert_eq!(three.min_tokens(&[1, 1, 1]).unwrap(), Some(5));
+    assert_eq!(three.min_tokens(&[1, 1, 3]).unwrap(), Some(8));
+    assert!(three.min_tokens(&COSTS).is_err());
+
+    // Checked against trying every number of presses.
+    let brute_force = |buttons: &[(u64, u64)], costs: [u64; 3], prize: (u64, u64)| {
+        let presses = || 0..=20;
+        let combinations =
+            presses().flat_map(|a| presses().flat_map(move |b| presses().map(move |c| [a, b, c])));
+        combinations
+            .filter(|n| {
+                (0..3)
+                    .map(|i| Vec2::new(buttons[i].0, buttons[i].1) * n[i])
+                    .sum::<Vec2>()
+                    == Vec2::new(prize.0, prize.1)
+            })
+            .map(|n| (0..3).map(|i| n[i] * costs[i]).sum::<u64>())
+            .min()
+    };
+    let buttons = [
+        [(3, 1), (1, 2), (2, 2)],
+        [(4, 0), (0, 6), (2, 3)],
+        [(2, 5), (5, 2), (7, 7)],
+        [(1, 0), (0, 1), (6, 4)],
+    ];
+    for buttons in buttons {
+        for prize in [(12, 10), (20, 18), (20, 20), (14, 19), (7, 3)] {
+            for costs in [[3, 1, 2], [1, 1, 5], [5, 4, 1]] {
+                let tokens = machine(&buttons, prize).min_tokens(&costs).unwrap();
+                assert_eq!(
+                    tokens,
+                    brute_force(&buttons, costs, prize),
+                    "{buttons:?} {prize:?} {costs:?}"
+                );
+            }
+        }
     }
+
+    // Part 2 sizes, with a third button and with a fourth (which is too many
+    // presses to try).
+    let far = 10000000000000;
+    let three = machine(&[(1, 0), (0, 1), (1, 1)], (far, far + 5));
+    assert_eq!(three.min_tokens(&[1, 1, 1]).unwrap(), Some(far + 5));
+    assert_eq!(three.min_tokens(&[1, 1, 3]).unwrap(), Some(2 * far + 5));
+    let four = machine(&[(1, 0), (0, 1), (1, 1), (2, 1)], (far, far));
+    assert!(four.min_tokens(&[1, 1, 1, 1]).is_err());
 }
@@ -1,20 +1,15 @@
 use color_eyre::eyre;
-use pathfinding::matrix::directions::DIRECTIONS_4;
 use std::{
     collections::{HashMap, HashSet},
-    str::FromStr,
-    thread::sleep,
-    time::Duration,
-};
-use winnow::{
-    ascii::{digit1, space0},
-    combinator::{alt, preceded},
+    fs,
+    path::Path,
 };
+use winnow::{ascii::space0, combinator::preceded};
 
-type Vec2 = nalgebra::Vector2<i64>;
+use crate::{answer::Answer, parse, vec2::Vec2};
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Hash)]
-struct Robot {
+pub(super) struct Robot {
     position: Vec2,
     velocity: Vec2,
 }
@@ -50,62 +45,98 @@ fn solve(robots: impl Iterator<Item = Robot>, size: Vec2, steps: u64) -> u64 {
 }
 
 #[elvish::solution(day = 14)]
-fn part1(input: &str) -> u64 {
-    solve(parse(input), Vec2::new(101, 103), 100)
+fn part1(input: &str) -> Answer<u64> {
+    Answer::new(|| Ok(solve(parse(input)?.into_iter(), Vec2::new(101, 103), 100)))
 }
 
-#[elvish::solution(day = 14, example = 281)]
-fn part2(input: &str) -> u64 {
-    let mut robots = parse(input).collect::<Vec<_>>();
-    let size = Vec2::new(101, 103);
+/// The variance of the robots' positions along one axis, after `steps`.
+fn variance(robots: &[Robot], steps: i64, axis: usize, size: i64) -> f64 {
+    let positions = robots
+        .iter()
+        .map(|robot| (robot.position[axis] + robot.velocity[axis] * steps).rem_euclid(size) as f64);
 
-    for i in 0.. {
-        if i > 8000 {
-            panic!()
-        }
+    let (count, sum, squares) =
+        positions.fold((0.0, 0.0, 0.0), |(count, sum, squares), position| {
+            (count + 1.0, sum + position, squares + position * position)
+        });
 
-        for robot in &mut robots {
-            robot.advance(1, size);
-        }
+    squares / count - (sum / count).powi(2)
+}
 
-        let mut queue = vec![&robots[0]];
-        let mut visited = HashSet::new();
-        while let Some(robot) = queue.pop() {
-            if !visited.insert(robot) {
-                continue;
-            }
-            for (dx, dy) in DIRECTIONS_4 {
-                let delta = Vec2::new(dx as i64, dy as i64);
-                if let Some(neighbor) = robots.iter().find(|n| robot.position + delta == n.position) {
-                    queue.push(neighbor);
-                }
-            }
-        }
+/// The step (below `size`) where the robots are the most bunched up along
+/// `axis`. Each axis repeats every `size` steps on its own.
+fn tightest(robots: &[Robot], axis: usize, size: i64) -> eyre::Result<i64> {
+    let variances = (0..size)
+        .map(|steps| variance(robots, steps, axis, size))
+        .collect::<Vec<_>>();
+
+    let mean = variances.iter().sum::<f64>() / variances.len() as f64;
+    let (steps, &min) = variances
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .ok_or_else(|| eyre::eyre!("The grid has no size"))?;
+
+    // When the robots draw something they're way more bunched up than usual,
+    // otherwise this would just be noise.
+    eyre::ensure!(
+        min < 0.75 * mean,
+        "The robots never bunch up along the {} axis",
+        ["x", "y"][axis]
+    );
+
+    Ok(steps as i64)
+}
 
-        // if visited.len() < 5 {
-        //     continue;
-        // }
-
-        let mut buffer = String::with_capacity((size.x * size.y * 2) as usize);
-        for y in 0..size.y {
-            for x in 0..size.x {
-                let p = Vec2::new(x, y);
-                if robots.iter().find(|robot| robot.position == p).is_some() {
-                    buffer.push('\u{2588}');
-                } else {
-                    buffer.push('.');
-                }
-            }
-
-            buffer.push('\n');
+/// The first step where the robots draw a picture, which is when they're
+/// bunched up along both axes. The steps for each axis are combined with the
+/// Chinese remainder theorem, so the sizes need to be coprime.
+fn picture_step(robots: &[Robot], size: Vec2) -> eyre::Result<u64> {
+    let x = tightest(robots, 0, size.x)?;
+    let y = tightest(robots, 1, size.y)?;
+
+    (0..size.y)
+        .map(|k| x + k * size.x)
+        .find(|steps| steps % size.y == y)
+        .map(|steps| steps as u64)
+        .ok_or_else(|| eyre::eyre!("The grid sizes {} and {} aren't coprime", size.x, size.y))
+}
+
+fn frame(robots: &[Robot], size: Vec2) -> String {
+    let positions = robots.iter().map(|robot| robot.position).collect::<HashSet<_>>();
+
+    let mut buffer = String::with_capacity((size.x * size.y * 2) as usize);
+    for y in 0..size.y {
+        for x in 0..size.x {
+            buffer.push(if positions.contains(&Vec2::new(x, y)) { '\u{2588}' } else { '.' });
         }
 
-        print!("\n\n\n\n{i}{buffer}");
+        buffer.push('\n');
+    }
+
+    buffer
+}
+
+#[elvish::solution(day = 14, example = 281)]
+fn part2(input: &str) -> Answer<u64> {
+    Answer::new(|| {
+        let robots = parse(input)?;
+        picture_step(&robots, Vec2::new(101, 103))
+    })
+}
+
+/// Writes the picture the robots draw to `day14.txt` in `dir`.
+pub fn inspect(input: &str, dir: &Path) -> eyre::Result<()> {
+    let mut robots = parse(input)?;
+    let size = Vec2::new(101, 103);
 
-        sleep(Duration::from_millis(60));
+    let steps = picture_step(&robots, size)?;
+    for robot in &mut robots {
+        robot.advance(steps, size);
     }
-    
-    panic!();
+
+    fs::write(dir.join("day14.txt"), format!("After {steps} seconds:\n{}", frame(&robots, size)))?;
+    Ok(())
 }
 
 elvish::example!(
@@ -127,40 +158,70 @@ elvish::example!(
 
 #[test]
 fn part1_example() {
-    let output = solve(parse(EXAMPLE_PART1), Vec2::new(11, 7), 100);
+    let output = solve(parse(EXAMPLE_PART1).unwrap().into_iter(), Vec2::new(11, 7), 100);
 
     assert_eq!(output, 12);
 }
 
-fn parse(input: &str) -> impl Iterator<Item = Robot> + use<'_> {
-    input.lines().map(|line| Robot::from_str(line).unwrap())
+#[test]
+fn finds_the_picture() {
+    let size = Vec2::new(101, 103);
+    let steps = 6123;
+
+    // Half of the robots end up in a 10x10 square, and the rest anywhere.
+    let robots = (0..400)
+        .map(|i: i64| {
+            let velocity = Vec2::new((i * 37) % 201 - 100, (i * 53) % 199 - 99);
+            let end = if i % 2 == 0 {
+                Vec2::new(40 + (i / 2) % 10, 50 + (i / 20) % 10)
+            } else {
+                Vec2::new((i * 71) % 101, (i * 29) % 103)
+            };
+
+            let position = (end - velocity * steps).zip_map(&size, |p, s| p.rem_euclid(s));
+            Robot { position, velocity }
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(picture_step(&robots, size).unwrap(), steps as u64);
+
+    let mut robots = robots;
+    for robot in &mut robots {
+        robot.advance(steps as u64, size);
+    }
+    assert!(frame(&robots, size).contains(&"\u{2588}".repeat(10)));
+
+    // Without the square it's only noise.
+    let noise = robots
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .copied()
+        .collect::<Vec<_>>();
+    let error = picture_step(&noise, size).unwrap_err();
+    assert!(
+        error.to_string().starts_with("The robots never bunch up"),
+        "{error}"
+    );
 }
 
-impl FromStr for Robot {
-    type Err = eyre::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use winnow::{prelude::*, seq};
+pub(super) fn parse(input: &str) -> eyre::Result<Vec<Robot>> {
+    use winnow::{prelude::*, seq};
 
-        fn natural(input: &mut &str) -> PResult<i64> {
-            digit1.parse_to().parse_next(input)
-        }
-
-        fn number(input: &mut &str) -> PResult<i64> {
-            alt((preceded('-', natural).map(|x| -x), natural)).parse_next(input)
-        }
-
-        fn vec2(input: &mut &str) -> PResult<Vec2> {
-            seq!(number, _: ',', number)
-                .map(|(x, y)| Vec2::new(x, y))
-                .parse_next(input)
-        }
+    fn vec2(input: &mut &str) -> PResult<Vec2> {
+        seq!(parse::int, _: ',', parse::int)
+            .map(|(x, y)| Vec2::new(x, y))
+            .parse_next(input)
+    }
 
-        let mut parser = seq!(Robot {
+    fn robot(input: &mut &str) -> PResult<Robot> {
+        seq!(Robot {
             position: preceded("p=", vec2),
             _: space0,
             velocity: preceded("v=", vec2),
-        });
-
-        Ok(parser.parse(s).map_err(|e| eyre::eyre!(e.to_string()))?)
+        })
+        .parse_next(input)
     }
+
+    Ok(parse::all(input, parse::lines(robot))?)
 }
@@ -1,280 +1,264 @@
+mod legend;
+mod replay;
+
 use std::{
     fmt::{Debug, Display, Write as _},
-    ops::{Add, Neg, Sub},
+    fs,
+    path::Path,
 };
 
-use ndarray::{Array2, ShapeBuilder as _};
+use color_eyre::eyre;
+use itertools::Itertools as _;
 
-type Vec2 = nalgebra::Vector2<usize>;
+use crate::{
+    answer::Answer,
+    grid::{Grid, Position},
+    parse::ParseError,
+    vec2::{Direction, Vec2, Vec2Ext as _},
+};
+use legend::{Block, Legend};
+use replay::Replay;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile<R> {
+    Empty,
     Wall,
     Robot,
     Rock(R),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Move(Vec2);
-
 trait Rock: Eq {
-    /// Parses the input into the grid corresponding to this rock
-    fn parse_grid(input: &str) -> Grid<Self>
-    where
-        Self: Sized;
-
-    /// The positions that this rock needs clear to be pushed.
-    fn pushes_into(&self, position: Vec2, mov: Move) -> impl IntoIterator<Item = Vec2>;
+    /// The positions of the whole rock this tile is part of, which move
+    /// together.
+    fn piece(&self, position: Vec2) -> impl IntoIterator<Item = Vec2>;
 
-    fn gps_coordinates(&self, position: Vec2) -> Vec2 {
-        position
+    /// Whether it stays put, like a wall.
+    fn fixed(&self) -> bool {
+        false
     }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Rock1;
-
-impl Rock for Rock1 {
-    fn parse_grid(input: &str) -> Grid<Self> {
-        let shape = (input.lines().count(), input.lines().next().unwrap().len());
-
-        let bytes = input
-            .bytes()
-            .filter(|&char| !char.is_ascii_whitespace())
-            .map(|char| match char {
-                b'#' => Some(Tile::Wall),
-                b'O' => Some(Tile::Rock(Rock1)),
-                b'@' => Some(Tile::Robot),
-                b'.' => None,
-                _ => panic!("Wrong char `{}`", char::from(char)),
-            })
-            .collect::<Vec<_>>();
-
-        let array = Array2::from_shape_vec(shape.strides((1, shape.1)), bytes).unwrap();
 
-        Grid { array }
+    /// Whether it drags along the rocks next to it when it moves.
+    fn sticky(&self) -> bool {
+        false
     }
 
-    fn pushes_into(&self, position: Vec2, mov: Move) -> impl IntoIterator<Item = Vec2> {
-        [position + mov]
-    }
+    /// Where the rock's GPS coordinates are measured from. Only one tile of
+    /// each rock should have them.
+    fn gps_coordinates(&self, position: Vec2) -> Option<Vec2>;
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Rock2 {
-    left: bool,
-}
-impl Rock2 {
-    fn partner(&self, position: Vec2) -> Vec2 {
-        if self.left {
-            position + Vec2::new(1, 0)
-        } else {
-            position - Vec2::new(1, 0) 
+impl<R: Display> Display for Tile<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tile::Empty => f.write_char('.'),
+            Tile::Wall => f.write_char('#'),
+            Tile::Robot => f.write_char('@'),
+            Tile::Rock(rock) => write!(f, "{rock}"),
         }
     }
 }
 
-impl Rock for Rock2 {
-    fn parse_grid(input: &str) -> Grid<Self> {
-        let shape = (
-            input.lines().next().unwrap().len() * 2,
-            input.lines().count(),
-        );
-
-        let bytes = input
-            .bytes()
-            .filter(|&char| !char.is_ascii_whitespace())
-            .flat_map(|char| match char {
-                b'#' => [Some(Tile::Wall); 2],
-                b'O' => [
-                    Some(Tile::Rock(Rock2 { left: true })),
-                    Some(Tile::Rock(Rock2 { left: false })),
-                ],
-                b'@' => [Some(Tile::Robot), None],
-                b'.' => [None; 2],
-                _ => panic!("Wrong char `{}`", char::from(char)),
-            })
-            .collect::<Vec<_>>();
-
-        let array = Array2::from_shape_vec(shape.strides((1, shape.0)), bytes).unwrap();
-
-        Grid { array }
-    }
-
-    fn pushes_into(&self, position: Vec2, mov: Move) -> impl IntoIterator<Item = Vec2> {
-        [position + mov, self.partner(position) + mov]
-    }
-    
-    fn gps_coordinates(&self, position: Vec2) -> Vec2 {
-        if self.left {
-            position
-        } else {
-            Vec2::zeros()
-        }
-    }
+/// What a move changed, so that it can be replayed or undone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Push {
+    pub direction: Direction,
+    /// Where each tile that moved ended up, in the order they moved. Empty if
+    /// the robot ran into a wall.
+    pub moved: Vec<Vec2>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Grid<R> {
-    array: Array2<Option<Tile<R>>>,
+struct Warehouse<R> {
+    grid: Grid<Tile<R>>,
 }
 
-impl<R> Grid<R> {
-    pub fn get(&self, position: Vec2) -> &Option<Tile<R>> {
-        &self.array[(position.x, position.y)]
+impl<R> Warehouse<R> {
+    pub fn get(&self, position: Vec2) -> &Tile<R> {
+        &self.grid[index(position)]
     }
+}
 
-    pub fn shape(&self) -> Vec2 {
-        let &[x, y] = self.array.shape() else {
-            unreachable!()
-        };
-
-        Vec2::new(x, y)
-    }
+/// The warehouse is surrounded by walls, so nothing can move out of it.
+fn index(position: Vec2) -> Position {
+    position
+        .to_position()
+        .expect("Positions should be inside the warehouse")
 }
 
-impl<R: Display> Display for Grid<R> {
+impl<R: Display> Display for Warehouse<R> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.shape().y {
-            for x in 0..self.shape().x {
-                match &self.array[(x, y)] {
-                    Some(Tile::Wall) => f.write_char('#')?,
-                    Some(Tile::Rock(rock)) => write!(f, "{rock}")?,
-                    Some(Tile::Robot) => f.write_char('@')?,
-                    None => f.write_char('.')?,
-                };
-            }
-
-            f.write_char('\n')?;
-        }
-
-        Ok(())
+        write!(f, "{}", self.grid)
     }
 }
 
-impl<R: Rock> Grid<R> {
+impl<R: Rock> Warehouse<R> {
     fn robot(&self) -> Vec2 {
-        let (x, y) = self
-            .array
-            .indexed_iter()
-            .find_map(|(pos, tile)| {
-                if matches!(tile, Some(Tile::Robot)) {
-                    Some(pos)
-                } else {
-                    None
-                }
-            })
+        let position = self
+            .grid
+            .find_all(&Tile::Robot)
+            .next()
             .expect("Grid should have exaclty one robot.");
 
-        Vec2::new(x, y)
+        Vec2::from_position(position)
     }
 
-    pub fn apply(&mut self, mov: Move)
+    /// Moves the robot (and whatever it pushes), returning what changed.
+    pub fn apply(&mut self, mov: Direction) -> Push
     where
         R: Debug,
     {
-        // List of position that are pushed-into.
-        let mut pushes = vec![self.robot() + mov];
+        let blocked = Push {
+            direction: mov,
+            moved: Vec::new(),
+        };
+
+        // The positions of everything that has to move, starting with the robot.
+        let mut moving = vec![self.robot()];
 
         let mut i = 0;
-        while let Some(&pos) = pushes.get(i) {
+        while let Some(&position) = moving.get(i) {
             i += 1;
-            let rock = match self.get(pos) {
-                Some(Tile::Wall) => return,
-                Some(Tile::Rock(rock)) => rock,
-                Some(Tile::Robot) => {
-                    unreachable!();
+
+            let mut dragged = Vec::new();
+            match self.get(position + mov) {
+                Tile::Wall => return blocked,
+                Tile::Rock(_) => dragged.push(position + mov),
+                // A rock wrapped around the robot, which moves out of the way
+                // first.
+                Tile::Robot | Tile::Empty => (),
+            }
+
+            if let Tile::Rock(rock) = self.get(position) {
+                if rock.fixed() {
+                    return blocked;
                 }
-                None => continue,
-            };
-
-            for push_into in rock.pushes_into(pos, mov) {
-                // Don't make the same push twice.
-                // TODO: Can I remove this?
-                if !pushes.contains(&push_into) {
-                    pushes.push(push_into);
+
+                dragged.extend(rock.piece(position));
+                if rock.sticky() {
+                    dragged.extend(
+                        Direction::ALL
+                            .map(|direction| position + direction)
+                            .into_iter()
+                            .filter(|&neighbor| matches!(self.get(neighbor), Tile::Rock(_))),
+                    );
+                }
+            }
+
+            for position in dragged {
+                // Don't move the same tile twice.
+                if !moving.contains(&position) {
+                    moving.push(position);
                 }
             }
         }
 
-        for to in pushes.into_iter().rev() {
+        // The ones in front go first, so that they make room for the rest.
+        let forward = mov.vec();
+        moving.sort_by_key(|position| std::cmp::Reverse(position.dot(&forward)));
+
+        let pushes = moving.into_iter().map(|from| from + mov).collect::<Vec<_>>();
+        for &to in &pushes {
             let from = to - mov;
-            assert_eq!(*self.get(to), None);
-            self.array.swap((from.x, from.y), (to.x, to.y));
+            assert_eq!(*self.get(to), Tile::Empty);
+            self.grid.swap(index(from), index(to));
         }
-    }
 
-    pub fn sum_of_gps_coordinates(&self) -> usize {
-        self.array
-            .indexed_iter()
-            .filter_map(|((x, y), tile)| {
-                if let Some(Tile::Rock(rock)) = tile {
-                    let c = rock.gps_coordinates(Vec2::new(x, y));
-                    Some(c.x + 100 * c.y)
-                } else {
-                    None
-                }
-            })
-            .sum()
+        Push {
+            direction: mov,
+            moved: pushes,
+        }
     }
-}
 
-impl Move {
-    fn from_char(char: u8) -> Self {
-        Move(match char {
-            b'>' => Vec2::new(1, 0),
-            b'<' => Vec2::new(usize::MAX, 0),
-            b'v' => Vec2::new(0, 1),
-            b'^' => Vec2::new(0, usize::MAX),
-            _ => panic!("wrong char `{}`", char::from(char)),
-        })
+    /// Makes the same changes as when `push` was applied.
+    pub fn redo(&mut self, push: &Push) {
+        for &to in &push.moved {
+            self.grid.swap(index(to - push.direction), index(to));
+        }
     }
-}
 
-impl Add<Move> for Vec2 {
-    type Output = Vec2;
-    fn add(self, rhs: Move) -> Self::Output {
-        self.zip_map(&rhs.0, |l, r| l.wrapping_add(r))
+    /// Reverts `push`, which has to be the last change made.
+    pub fn undo(&mut self, push: &Push) {
+        for &to in push.moved.iter().rev() {
+            self.grid.swap(index(to - push.direction), index(to));
+        }
     }
-}
 
-impl Sub<Move> for Vec2 {
-    type Output = Vec2;
-    fn sub(self, rhs: Move) -> Self::Output {
-        self + -rhs
-    }
-}
+    pub fn sum_of_gps_coordinates(&self) -> i64 {
+        self.grid
+            .iter()
+            .filter_map(|(position, tile)| {
+                let Tile::Rock(rock) = tile else {
+                    return None;
+                };
 
-impl Neg for Move {
-    type Output = Move;
-    fn neg(self) -> Self::Output {
-        Move(self.0.map(|x| -(x as isize) as usize))
+                let c = rock.gps_coordinates(Vec2::from_position(position))?;
+                Some(c.x + 100 * c.y)
+            })
+            .sum()
     }
 }
 
-
-fn solve<R: Rock + Debug + Display>(input: &str) -> usize {
-    let (mut grid, moves) = parse::<R>(input);
+fn solve(input: &str, legend: &Legend) -> eyre::Result<i64> {
+    let (mut warehouse, moves) = parse(input, legend)?;
 
     for m in moves {
-        // println!("{grid}");
-        grid.apply(m);
+        warehouse.apply(m);
     }
 
-    grid.sum_of_gps_coordinates()
+    Ok(warehouse.sum_of_gps_coordinates())
 }
 
 #[elvish::solution(day = 15, example = 10092)]
-fn part1(input: &str) -> usize {
-    solve::<Rock1>(input)
+fn part1(input: &str) -> Answer<i64> {
+    solve(input, &Legend::part1()).into()
 }
 
 #[elvish::solution(day = 15, example = 9021)]
-fn part2(input: &str) -> usize {
-    solve::<Rock2>(input)
+fn part2(input: &str) -> Answer<i64> {
+    solve(input, &Legend::part2()).into()
 }
 
-// 74478585072604: Too high
+/// Writes the warehouse after some of the moves of part 2 to `day15.txt` in
+/// `dir`, in the same format as the puzzle's walkthrough, and as an asciicast
+/// to `day15.cast`. See [`replay`].
+///
+/// The moves are picked by a `day15-frames` file in `dir` (like `0..100,500`),
+/// or are the first 100 and the last one without it, since every move of the
+/// real input is a lot of frames. If there's a `day15-trace.txt`, the run is
+/// checked against it.
+pub fn inspect(input: &str, dir: &Path) -> eyre::Result<()> {
+    let (warehouse, moves) = parse(input, &Legend::part2())?;
+    let mut replay = Replay::new(warehouse, moves);
+
+    let selection = dir.join("day15-frames");
+    let frames = if selection.exists() {
+        replay::selection(&fs::read_to_string(selection)?)?
+    } else {
+        let last = replay.moves().len();
+        (0..=last.min(100)).chain([last]).dedup().collect()
+    };
+
+    fs::write(dir.join("day15.txt"), replay.frames(frames.iter().copied()))?;
+    fs::write(dir.join("day15.cast"), replay.asciicast(frames, 0.1)?)?;
+
+    // Checks the run against a trace written like the walkthrough, if there is
+    // one.
+    let trace = dir.join("day15-trace.txt");
+    if trace.exists() {
+        let reference = replay::parse_trace(&fs::read_to_string(trace)?)?;
+        if let Some(position) = replay.first_difference(&reference) {
+            replay.seek(position);
+            eyre::bail!(
+                "The warehouse differs from day15-trace.txt after {position} moves:\n{}\n\n\
+                 The trace has:\n{}",
+                replay.frame(),
+                reference[position]
+            );
+        }
+    }
+
+    Ok(())
+}
 
 elvish::example!(
     "
@@ -317,36 +301,154 @@ const SMOL: &str = elvish::indoc! {
     "
 };
 
+/// The steps of [`SMOL`] in the puzzle's description.
+#[cfg(test)]
+const SMOL_TRACE: &str = elvish::indoc! {
+    "
+        Initial state:
+        ##############
+        ##......##..##
+        ##..........##
+        ##....[][]@.##
+        ##....[]....##
+        ##..........##
+        ##############
+
+        Move <:
+        ##############
+        ##......##..##
+        ##..........##
+        ##...[][]@..##
+        ##....[]....##
+        ##..........##
+        ##############
+
+        Move v:
+        ##############
+        ##......##..##
+        ##..........##
+        ##...[][]...##
+        ##....[].@..##
+        ##..........##
+        ##############
+
+        Move v:
+        ##############
+        ##......##..##
+        ##..........##
+        ##...[][]...##
+        ##....[]....##
+        ##.......@..##
+        ##############
+
+        Move <:
+        ##############
+        ##......##..##
+        ##..........##
+        ##...[][]...##
+        ##....[]....##
+        ##......@...##
+        ##############
+
+        Move <:
+        ##############
+        ##......##..##
+        ##..........##
+        ##...[][]...##
+        ##....[]....##
+        ##.....@....##
+        ##############
+
+        Move ^:
+        ##############
+        ##......##..##
+        ##...[][]...##
+        ##....[]....##
+        ##.....@....##
+        ##..........##
+        ##############
+
+        Move ^:
+        ##############
+        ##......##..##
+        ##...[][]...##
+        ##....[]....##
+        ##.....@....##
+        ##..........##
+        ##############
+
+        Move <:
+        ##############
+        ##......##..##
+        ##...[][]...##
+        ##....[]....##
+        ##....@.....##
+        ##..........##
+        ##############
+
+        Move <:
+        ##############
+        ##......##..##
+        ##...[][]...##
+        ##....[]....##
+        ##...@......##
+        ##..........##
+        ##############
+
+        Move ^:
+        ##############
+        ##......##..##
+        ##...[][]...##
+        ##...@[]....##
+        ##..........##
+        ##..........##
+        ##############
+
+        Move ^:
+        ##############
+        ##...[].##..##
+        ##...@.[]...##
+        ##....[]....##
+        ##..........##
+        ##..........##
+        ##############
+    "
+};
+
 #[test]
-fn part2_smol() {
-    println!("{}", part2(SMOL));
+fn examples() {
+    assert_eq!(part1(EXAMPLE_PART1).into_result().unwrap(), 10092);
+    assert_eq!(part2(EXAMPLE_PART2).into_result().unwrap(), 9021);
 }
 
-impl Display for Rock1 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_char('O')
-    }
-}
+#[test]
+fn part2_smol() {
+    let (warehouse, moves) = parse(SMOL, &Legend::part2()).unwrap();
+    let mut replay = Replay::new(warehouse, moves);
 
-impl Display for Rock2 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.left {
-            true => f.write_char('['),
-            false => f.write_char(']'),
-        }
-    }
+    let reference = replay::parse_trace(SMOL_TRACE).unwrap();
+    assert_eq!(replay.first_difference(&reference), None);
+
+    println!("{}", part2(SMOL).into_result().unwrap());
 }
 
-fn parse<R: Rock>(input: &str) -> (Grid<R>, impl Iterator<Item = Move> + use<'_, R>) {
-    let (grid, instructions) = {
-        let mut parts = input.split("\n\n");
-        (parts.next().unwrap(), parts.next().unwrap())
+fn parse(input: &str, legend: &Legend) -> eyre::Result<(Warehouse<Block>, Vec<Direction>)> {
+    let Some((grid, instructions)) = input.split_once("\n\n") else {
+        eyre::bail!("Expected the warehouse and the moves separated by a blank line");
     };
 
+    let grid = legend.parse(grid)?;
+    grid.find(&Tile::Robot)?;
+
     let instructions = instructions
-        .bytes()
-        .filter(|&char| !char.is_ascii_whitespace())
-        .map(Move::from_char);
+        .char_indices()
+        .filter(|(_, char)| !char.is_ascii_whitespace())
+        .map(|(i, char)| {
+            Direction::from_char(char).ok_or_else(|| {
+                ParseError::at(input, &instructions[i..], format_args!("Unexpected move `{char}`"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-    (Rock::parse_grid(grid), instructions)
+    Ok((Warehouse { grid }, instructions))
 }
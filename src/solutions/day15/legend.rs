@@ -0,0 +1,355 @@
+//! What the characters of a map mean, so different warehouses can be described
+//! without writing a parser for each.
+//!
+//! Every character of the map first widens into a few others (like `O` into
+//! `[]` in part 2), and then each of those is a tile. Rock characters say which
+//! of their neighbours they join with, so `[` joins the `]` to its right and
+//! the two move as a single rock. Rocks can be any shape this way, such as a
+//! wider `[==]` or an `L` drawn with box characters.
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use color_eyre::eyre;
+use ndarray::Array2;
+
+use super::{Rock, Tile};
+use crate::{
+    grid::Grid,
+    vec2::{Direction, Vec2, Vec2Ext as _},
+};
+
+/// What a rock character does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RockKind {
+    /// The neighbours that are part of the same rock, if they join back.
+    pub joins: &'static [Direction],
+    /// Can't be pushed, like a wall.
+    pub fixed: bool,
+    /// Drags along every rock it touches.
+    pub sticky: bool,
+}
+
+pub struct Legend {
+    /// What each character of the map widens into. Characters that aren't
+    /// here stay as they are.
+    expansion: HashMap<char, &'static str>,
+    tiles: HashMap<char, Tile<RockKind>>,
+}
+
+impl Legend {
+    /// A legend for `#`, `.` and `@`, without any rocks.
+    pub fn new() -> Self {
+        Self {
+            expansion: HashMap::new(),
+            tiles: HashMap::from([('#', Tile::Wall), ('.', Tile::Empty), ('@', Tile::Robot)]),
+        }
+    }
+
+    /// Single tile boxes, `O`.
+    pub fn part1() -> Self {
+        Self::new().with_rock('O', RockKind::default())
+    }
+
+    /// Everything twice as wide, with boxes as `[]`.
+    pub fn part2() -> Self {
+        Self::new()
+            .with_expansion('#', "##")
+            .with_expansion('.', "..")
+            .with_expansion('@', "@.")
+            .with_expansion('O', "[]")
+            .with_rock(
+                '[',
+                RockKind {
+                    joins: &[Direction::Right],
+                    ..Default::default()
+                },
+            )
+            .with_rock(
+                ']',
+                RockKind {
+                    joins: &[Direction::Left],
+                    ..Default::default()
+                },
+            )
+    }
+
+    /// Makes `char` widen into `tiles`.
+    pub fn with_expansion(mut self, char: char, tiles: &'static str) -> Self {
+        self.expansion.insert(char, tiles);
+        self
+    }
+
+    pub fn with_rock(mut self, char: char, rock: RockKind) -> Self {
+        self.tiles.insert(char, Tile::Rock(rock));
+        self
+    }
+
+    fn expand(&self, char: char) -> Option<Vec<char>> {
+        match self.expansion.get(&char) {
+            Some(tiles) => Some(tiles.chars().collect()),
+            None => self.tiles.contains_key(&char).then(|| vec![char]),
+        }
+    }
+
+    /// Parses and widens the map, working out which tiles make up each rock.
+    pub fn parse(&self, input: &str) -> eyre::Result<Grid<Tile<Block>>> {
+        let map = Grid::parse_with(input, |char| self.expand(char))?;
+
+        let width = map.iter().map(|(_, tiles)| tiles.len()).max().unwrap_or(1);
+        if let Some((_, tiles)) = map.iter().find(|(_, tiles)| tiles.len() != width) {
+            eyre::bail!(
+                "Every character should widen into {width} tiles, not `{}`",
+                String::from_iter(tiles)
+            );
+        }
+
+        let chars = Grid::from_array(Array2::from_shape_fn(
+            (map.height(), map.width() * width),
+            |(y, x)| map.get((x / width, y)).unwrap()[x % width],
+        ));
+
+        if let Some((position, char)) = chars
+            .iter()
+            .find(|(_, char)| !self.tiles.contains_key(char))
+        {
+            eyre::bail!("`{char}` (at {position:?} once widened) isn't in the legend");
+        }
+
+        let kinds = chars.map(|char| self.tiles[char]);
+        let mut blocks = blocks(&chars, &kinds);
+
+        Ok(Grid::from_array(Array2::from_shape_fn(
+            (kinds.height(), kinds.width()),
+            |(y, x)| match kinds.get((x, y)).unwrap() {
+                Tile::Empty => Tile::Empty,
+                Tile::Wall => Tile::Wall,
+                Tile::Robot => Tile::Robot,
+                Tile::Rock(_) => Tile::Rock(blocks.remove(&(x, y)).unwrap()),
+            },
+        )))
+    }
+}
+
+/// A tile of a rock, described by a [`Legend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    char: char,
+    kind: RockKind,
+    /// The tiles of the whole rock, from the top left corner of its bounding
+    /// box.
+    piece: Arc<[Vec2]>,
+    /// From this tile to that corner.
+    corner: Vec2,
+    /// Whether this is the rock's first tile, which its GPS coordinates go
+    /// with.
+    first: bool,
+}
+
+/// Groups the rocks' tiles with the neighbours they join.
+fn blocks(chars: &Grid<char>, kinds: &Grid<Tile<RockKind>>) -> HashMap<(usize, usize), Block> {
+    let joined = |position: Vec2, direction: Direction| match (
+        kinds.at(position),
+        kinds.at(position + direction),
+    ) {
+        (Some(Tile::Rock(from)), Some(Tile::Rock(to))) => {
+            from.joins.contains(&direction) && to.joins.contains(&direction.opposite())
+        }
+        _ => false,
+    };
+
+    let mut blocks = HashMap::new();
+    for (start, tile) in kinds.iter() {
+        if !matches!(tile, Tile::Rock(_)) || blocks.contains_key(&start) {
+            continue;
+        }
+
+        let start = Vec2::from_position(start);
+        let mut tiles = vec![start];
+        let mut i = 0;
+        while let Some(&tile) = tiles.get(i) {
+            i += 1;
+
+            for direction in Direction::ALL {
+                if joined(tile, direction) && !tiles.contains(&(tile + direction)) {
+                    tiles.push(tile + direction);
+                }
+            }
+        }
+
+        let corner = Vec2::new(
+            tiles.iter().map(|tile| tile.x).min().unwrap(),
+            tiles.iter().map(|tile| tile.y).min().unwrap(),
+        );
+        let piece = tiles.iter().map(|tile| tile - corner).collect::<Arc<[_]>>();
+
+        for &tile in &tiles {
+            let position = tile.to_position().unwrap();
+            let Some(&Tile::Rock(kind)) = kinds.get(position) else {
+                unreachable!()
+            };
+
+            let block = Block {
+                char: *chars.get(position).unwrap(),
+                kind,
+                piece: piece.clone(),
+                corner: corner - tile,
+                first: tile == start,
+            };
+            blocks.insert(position, block);
+        }
+    }
+
+    blocks
+}
+
+impl Rock for Block {
+    fn piece(&self, position: Vec2) -> impl IntoIterator<Item = Vec2> {
+        let corner = position + self.corner;
+        self.piece.iter().map(move |tile| corner + tile)
+    }
+
+    fn fixed(&self) -> bool {
+        self.kind.fixed
+    }
+
+    fn sticky(&self) -> bool {
+        self.kind.sticky
+    }
+
+    fn gps_coordinates(&self, position: Vec2) -> Option<Vec2> {
+        self.first.then(|| position + self.corner)
+    }
+}
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.char)
+    }
+}
+
+#[test]
+fn shapes() {
+    use super::Warehouse;
+
+    let run = |legend: &Legend, map: &str, moves: &str| {
+        let mut warehouse = Warehouse {
+            grid: legend.parse(map).unwrap(),
+        };
+        for mov in moves.chars() {
+            warehouse.apply(Direction::from_char(mov).unwrap());
+        }
+        warehouse
+    };
+
+    let wide = Legend::new()
+        .with_rock(
+            '[',
+            RockKind {
+                joins: &[Direction::Right],
+                ..Default::default()
+            },
+        )
+        .with_rock(
+            '=',
+            RockKind {
+                joins: &[Direction::Left, Direction::Right],
+                ..Default::default()
+            },
+        )
+        .with_rock(
+            ']',
+            RockKind {
+                joins: &[Direction::Left],
+                ..Default::default()
+            },
+        );
+    assert_eq!(run(&wide, "#..[=]@#", "<<<").to_string(), "#[=]@..#\n");
+
+    let widened = Legend::part2()
+        .with_expansion('#', "#")
+        .with_expansion('.', ".")
+        .with_expansion('@', "@");
+    let error = widened.parse("#O.@#").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Every character should widen into 2 tiles, not `#`"
+    );
+
+    // An L made out of three tiles, which moves all at once.
+    let l = Legend::new()
+        .with_rock(
+            'a',
+            RockKind {
+                joins: &[Direction::Down],
+                ..Default::default()
+            },
+        )
+        .with_rock(
+            'b',
+            RockKind {
+                joins: &[Direction::Up, Direction::Right],
+                ..Default::default()
+            },
+        )
+        .with_rock(
+            'c',
+            RockKind {
+                joins: &[Direction::Left],
+                ..Default::default()
+            },
+        );
+    let map = "######\n#....#\n#a...#\n#bc..#\n#.@..#\n######";
+    let warehouse = run(&l, map, "^");
+    assert_eq!(
+        warehouse.to_string(),
+        "######\n#a...#\n#bc..#\n#.@..#\n#....#\n######\n"
+    );
+    assert_eq!(warehouse.sum_of_gps_coordinates(), 101);
+    assert_eq!(run(&l, map, "^^").to_string(), warehouse.to_string());
+
+    // A C wrapped around the robot, so that pushing its top arm drags the
+    // bottom one into where the robot was.
+    let joins: [(char, &[Direction]); 5] = [
+        ('a', &[Direction::Right, Direction::Down]),
+        ('b', &[Direction::Left]),
+        ('c', &[Direction::Up, Direction::Down]),
+        ('d', &[Direction::Up, Direction::Right]),
+        ('e', &[Direction::Left]),
+    ];
+    let c = joins
+        .into_iter()
+        .fold(Legend::new(), |legend, (char, joins)| {
+            legend.with_rock(
+                char,
+                RockKind {
+                    joins,
+                    ..Default::default()
+                },
+            )
+        });
+    let warehouse = run(&c, "#####\n#...#\n#ab.#\n#c@.#\n#de.#\n#####", "^");
+    assert_eq!(
+        warehouse.to_string(),
+        "#####\n#ab.#\n#c@.#\n#de.#\n#...#\n#####\n"
+    );
+    assert_eq!(warehouse.sum_of_gps_coordinates(), 101);
+
+    let fixed = Legend::part1().with_rock(
+        'X',
+        RockKind {
+            fixed: true,
+            ..Default::default()
+        },
+    );
+    assert_eq!(run(&fixed, "#.OX.@#", "<<<").to_string(), "#.OX@.#\n");
+
+    let sticky = Legend::part1().with_rock(
+        'S',
+        RockKind {
+            sticky: true,
+            ..Default::default()
+        },
+    );
+    let warehouse = run(&sticky, "######\n#..O.#\n#..S@#\n######", "<");
+    assert_eq!(warehouse.to_string(), "######\n#.O..#\n#.S@.#\n######\n");
+}
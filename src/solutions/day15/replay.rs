@@ -0,0 +1,245 @@
+//! Records the robot's moves as the tiles each one pushed, so the run can be
+//! stepped forwards and backwards, exported as frames, or checked against a
+//! reference trace (like the one in the puzzle's description).
+//!
+//! Frames are written like in the description: `Initial state:` or `Move <:`
+//! and then the warehouse, with a blank line between frames.
+
+use std::fmt::{Debug, Display};
+
+use color_eyre::eyre;
+
+use super::{Push, Rock, Warehouse};
+use crate::vec2::Direction;
+
+pub struct Replay<R> {
+    warehouse: Warehouse<R>,
+    moves: Vec<Direction>,
+    /// What each move changed, for the moves that have been applied at some
+    /// point (which might be more than the current position).
+    pushes: Vec<Push>,
+    position: usize,
+}
+
+impl<R: Rock + Debug + Display> Replay<R> {
+    pub fn new(warehouse: Warehouse<R>, moves: Vec<Direction>) -> Self {
+        Self {
+            warehouse,
+            moves,
+            pushes: Vec::new(),
+            position: 0,
+        }
+    }
+
+    pub fn moves(&self) -> &[Direction] {
+        &self.moves
+    }
+
+    /// Applies the next move, or returns `None` if there aren't any more.
+    pub fn forward(&mut self) -> Option<&Push> {
+        let &direction = self.moves.get(self.position)?;
+
+        if let Some(push) = self.pushes.get(self.position) {
+            self.warehouse.redo(push);
+        } else {
+            let push = self.warehouse.apply(direction);
+            self.pushes.push(push);
+        }
+
+        self.position += 1;
+        Some(&self.pushes[self.position - 1])
+    }
+
+    /// Undoes the last move, or returns `None` if it's at the start.
+    pub fn back(&mut self) -> Option<&Push> {
+        self.position = self.position.checked_sub(1)?;
+
+        let push = &self.pushes[self.position];
+        self.warehouse.undo(push);
+        Some(push)
+    }
+
+    /// Goes to right after move `position` (or to the end, if there aren't
+    /// that many moves).
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.moves.len());
+
+        while self.position < position {
+            self.forward();
+        }
+        while self.position > position {
+            self.back();
+        }
+    }
+
+    /// The current state, as a frame of a trace.
+    pub fn frame(&self) -> String {
+        match self.position.checked_sub(1) {
+            None => format!("Initial state:\n{}", self.warehouse),
+            Some(last) => format!("Move {}:\n{}", self.moves[last], self.warehouse),
+        }
+    }
+
+    /// The frames after each move in `selection`, as text. It ends up back
+    /// where it was.
+    pub fn frames(&mut self, selection: impl IntoIterator<Item = usize>) -> String {
+        let start = self.position;
+
+        let mut frames = Vec::new();
+        for position in selection {
+            self.seek(position);
+            frames.push(self.frame());
+        }
+
+        self.seek(start);
+        frames.join("\n")
+    }
+
+    /// The frames after each move in `selection` as an [asciicast], showing
+    /// each for `seconds`. Play it with `asciinema play`.
+    ///
+    /// [asciicast]: https://docs.asciinema.org/manual/asciicast/v2/
+    pub fn asciicast(
+        &mut self,
+        selection: impl IntoIterator<Item = usize>,
+        seconds: f64,
+    ) -> eyre::Result<String> {
+        let start = self.position;
+        let grid = &self.warehouse.grid;
+
+        let header = serde_json::json!({
+            "version": 2,
+            "width": grid.width(),
+            "height": grid.height() + 1,
+        });
+        let mut lines = vec![serde_json::to_string(&header)?];
+
+        for (index, position) in selection.into_iter().enumerate() {
+            self.seek(position);
+
+            // Clear the screen and draw the frame from the top left.
+            let frame = format!("\x1b[H\x1b[2J{}", self.frame().replace('\n', "\r\n"));
+            let event = serde_json::json!([index as f64 * seconds, "o", frame]);
+            lines.push(serde_json::to_string(&event)?);
+        }
+
+        self.seek(start);
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// The first position where the warehouse isn't the same as in `reference`
+    /// (as given by [`parse_trace`]), or `None` if they agree for as long as
+    /// they both go. It ends up back where it was.
+    pub fn first_difference(&mut self, reference: &[String]) -> Option<usize> {
+        let start = self.position;
+        self.seek(0);
+
+        // They can go wrong and then end up the same again, so every state
+        // gets checked.
+        let mut difference = None;
+        for (position, expected) in reference.iter().enumerate() {
+            if self.warehouse.to_string().trim_end() != *expected {
+                difference = Some(position);
+                break;
+            }
+            if self.forward().is_none() {
+                break;
+            }
+        }
+
+        self.seek(start);
+        difference
+    }
+}
+
+/// The warehouses in a trace written like the puzzle's description, one for
+/// the initial state and one after each move.
+pub fn parse_trace(trace: &str) -> eyre::Result<Vec<String>> {
+    let mut warehouses = Vec::new();
+
+    for (index, frame) in trace.trim().split("\n\n").enumerate() {
+        let Some((header, warehouse)) = frame.split_once('\n') else {
+            eyre::bail!("Frame {index} of the trace has no warehouse");
+        };
+
+        let expected = if index == 0 { "Initial state:" } else { "Move _:" };
+        let header_ok = match index {
+            0 => header.trim() == "Initial state:",
+            _ => header
+                .trim()
+                .strip_prefix("Move ")
+                .and_then(|rest| rest.strip_suffix(':'))
+                .is_some_and(|direction| direction.len() == 1),
+        };
+        eyre::ensure!(
+            header_ok,
+            "Frame {index} of the trace should start with `{expected}`, not `{header}`"
+        );
+
+        warehouses.push(warehouse.trim_end().to_string());
+    }
+
+    Ok(warehouses)
+}
+
+/// Which frames to export, like `0..100,500,1000..=1010`.
+pub fn selection(text: &str) -> eyre::Result<Vec<usize>> {
+    let mut frames = Vec::new();
+
+    for part in text.split(',').map(str::trim) {
+        if let Some((start, end)) = part.split_once("..=") {
+            frames.extend(start.parse::<usize>()?..=end.parse()?);
+        } else if let Some((start, end)) = part.split_once("..") {
+            frames.extend(start.parse::<usize>()?..end.parse()?);
+        } else {
+            frames.push(part.parse()?);
+        }
+    }
+
+    Ok(frames)
+}
+
+#[test]
+fn stepping() {
+    let (warehouse, moves) = super::parse(super::SMOL, &super::Legend::part2()).unwrap();
+    let start = warehouse.clone();
+    let mut replay = Replay::new(warehouse, moves);
+
+    replay.seek(100);
+    assert_eq!(replay.position, 11);
+    let end = replay.warehouse.clone();
+
+    assert!(replay.forward().is_none());
+    assert_eq!(replay.back().unwrap().direction, Direction::Up);
+    replay.seek(0);
+    assert_eq!(replay.warehouse, start);
+    assert!(replay.back().is_none());
+
+    replay.seek(11);
+    assert_eq!(replay.warehouse, end);
+
+    let frames = replay.frames([0, 1]);
+    assert!(frames.starts_with("Initial state:\n"), "{frames}");
+    assert!(frames.contains("\n\nMove <:\n"), "{frames}");
+    assert_eq!(replay.position, 11);
+
+    let cast = replay.asciicast(0..3, 0.5).unwrap();
+    assert_eq!(cast.lines().count(), 4);
+    assert!(cast.starts_with(r#"{"height":8,"version":2,"width":14}"#), "{cast}");
+
+    // A trace that goes wrong after the 4th move.
+    let mut reference = parse_trace(super::SMOL_TRACE).unwrap();
+    for warehouse in &mut reference[5..] {
+        *warehouse = warehouse.replacen('.', "#", 1);
+    }
+    assert_eq!(replay.first_difference(&reference), Some(5));
+    assert_eq!(replay.position, 11);
+
+    // And one that only goes wrong for the 2nd move.
+    let mut reference = parse_trace(super::SMOL_TRACE).unwrap();
+    reference[2] = reference[2].replacen('.', "#", 1);
+    assert_eq!(replay.first_difference(&reference), Some(2));
+
+    assert!(parse_trace("Move <:\n###").is_err());
+    assert_eq!(selection("0..2, 5,7..=8").unwrap(), [0, 1, 5, 7, 8]);
+}
@@ -1,49 +1,53 @@
 use std::collections::HashSet;
 
-use nalgebra::vector;
-use ndarray::{Array2, ShapeBuilder as _};
+use color_eyre::eyre::{self, OptionExt as _};
 use pathfinding::prelude::{astar_bag, dijkstra};
 
+use crate::{
+    answer::Answer,
+    grid::Grid,
+    vec2::{Direction, Vec2, Vec2Ext as _},
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Node {
-    position: IVec2,
-    direction: IVec2,
+pub(super) struct Node {
+    position: Vec2,
+    direction: Direction,
 }
 
-type IVec2 = nalgebra::Vector2<i64>;
+/// Whether each tile can be walked on.
+type Maze = Grid<bool>;
 
-fn successors(grid: &Array2<Option<()>>, node: &Node) -> impl Iterator<Item = (Node, u64)> {
+fn successors(grid: &Maze, node: &Node) -> impl Iterator<Item = (Node, u64)> {
     let Node {
         position,
         direction,
     } = *node;
 
-    let straight = position.cast() + direction;
-    let straight = grid[(straight.x as usize, straight.y as usize)].map(|_| {
-        (
-            Node {
-                position: straight,
-                direction,
-            },
-            1,
-        )
-    });
+    let straight = position + direction;
+    let is_open = |p: Vec2| grid.at(p) == Some(&true);
+
+    let straight = is_open(straight).then_some((
+        Node {
+            position: straight,
+            direction,
+        },
+        1,
+    ));
 
-    let turn_a = IVec2::new(direction.y, -direction.x);
-    let turn_b = -turn_a;
 
     [
         (
             Node {
                 position,
-                direction: turn_a,
+                direction: direction.turn_left(),
             },
             1000u64,
         ),
         (
             Node {
                 position,
-                direction: turn_b,
+                direction: direction.turn_right(),
             },
             1000u64,
         ),
@@ -53,39 +57,43 @@ fn successors(grid: &Array2<Option<()>>, node: &Node) -> impl Iterator<Item = (N
 }
 
 #[elvish::solution(day = 16, example = [7036, 11048])]
-fn part1(input: &str) -> u64 {
-    let (grid, start, end) = parse(&input);
-
-    let (_path, total_cost) = dijkstra(
-        &start,
-        |node| successors(&grid, node),
-        |node| node.position == end,
-    )
-    .unwrap();
+fn part1(input: &str) -> Answer<u64> {
+    Answer::new(|| {
+        let (grid, start, end) = parse(&input)?;
+
+        let (_path, total_cost) = dijkstra(
+            &start,
+            |node| successors(&grid, node),
+            |node| node.position == end,
+        )
+        .ok_or_eyre("There's no path to the end")?;
 
-    total_cost
+        Ok(total_cost)
+    })
 }
 
 #[elvish::solution(day = 16, example = [45, 64])]
-fn part2(input: &str) -> usize {
-    let (grid, start, end) = parse(&input);
-
-    let (paths, _total_cost) = astar_bag(
-        &start,
-        |node| successors(&grid, node),
-        |_| 0, // Just to get bag of paths more conviniently
-        |node| node.position == end,
-    )
-    .unwrap();
-
-    let mut potentially_optimal = HashSet::new();
-    for path in paths {
-        for node in path {
-            potentially_optimal.insert(node.position);
+fn part2(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let (grid, start, end) = parse(&input)?;
+
+        let (paths, _total_cost) = astar_bag(
+            &start,
+            |node| successors(&grid, node),
+            |_| 0, // Just to get bag of paths more conviniently
+            |node| node.position == end,
+        )
+        .ok_or_eyre("There's no path to the end")?;
+
+        let mut potentially_optimal = HashSet::new();
+        for path in paths {
+            for node in path {
+                potentially_optimal.insert(node.position);
+            }
         }
-    }
 
-    potentially_optimal.len()
+        Ok(potentially_optimal.len())
+    })
 }
 
 elvish::example!(
@@ -167,38 +175,14 @@ elvish::example!(
     ",
 );
 
-fn parse(input: &str) -> (Array2<Option<()>>, Node, IVec2) {
-    let shape = (input.lines().count(), input.lines().next().unwrap().len());
-
-    let mut start = None;
-    let mut end = None;
-    let bytes = input
-        .bytes()
-        .filter(|&char| !char.is_ascii_whitespace())
-        .enumerate()
-        .map(|(i, char)| match char {
-            b'#' => None,
-            b'.' => Some(()),
-            b'S' => {
-                start = Some(vector![i % shape.1, i / shape.1].cast());
-                Some(())
-            }
-            b'E' => {
-                end = Some(vector![i % shape.1, i / shape.1].cast());
-                Some(())
-            }
-            _ => panic!("Wrong char `{}`", char::from(char)),
-        })
-        .collect::<Vec<_>>();
+pub(super) fn parse(input: &str) -> eyre::Result<(Maze, Node, Vec2)> {
+    let grid = Grid::parse_with(input, |char| "#.SE".contains(char).then_some(char))?;
 
-    let array = Array2::from_shape_vec(shape.strides((1, shape.1)), bytes).unwrap();
+    let start = Node {
+        position: Vec2::from_position(grid.find(&'S')?),
+        direction: Direction::Right,
+    };
+    let end = Vec2::from_position(grid.find(&'E')?);
 
-    (
-        array,
-        Node {
-            position: start.unwrap(),
-            direction: IVec2::new(1, 0),
-        },
-        end.unwrap(),
-    )
+    Ok((grid.map(|&char| char != '#'), start, end))
 }
@@ -1,85 +1,159 @@
+mod asm;
+mod compiled;
+mod debugger;
+mod quine;
+mod symbolic;
+
 use color_eyre::eyre;
 use itertools::Itertools as _;
-use std::{collections::VecDeque, fmt::Write as _, str::FromStr};
-use strum::{EnumString, FromRepr};
+use std::{fmt, fs, path::Path, str::FromStr};
+use strum::{EnumString, FromRepr, IntoStaticStr};
+
+use crate::{
+    answer::Answer,
+    parse::{self, uint, ParseError},
+};
+use debugger::{Debugger, Stop};
 use winnow::{
-    ascii::{digit1, multispace0},
-    combinator::{preceded, separated},
+    ascii::{digit1, line_ending},
+    error::StrContext,
     PResult, Parser as _,
 };
 
-/// The quote-on-quote "`usize`" for the machine. Only 3 bits.
-#[derive(Clone, Copy, PartialEq, Eq)]
-struct Usize {
-    bits: [bool; 3],
-}
+/// The quote-on-quote "`usize`" for the machine. Only 3 bits, packed in a
+/// `u8` that's always below 8.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub(super) struct U3(u8);
 
-impl std::fmt::Debug for Usize {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if f.alternate() {
-            return write!(f, "{}", self.value());
+impl U3 {
+    pub const ZERO: Self = Self(0);
+
+    /// `None` if `value` doesn't fit in 3 bits.
+    pub const fn new(value: u8) -> Option<Self> {
+        if value < 8 {
+            Some(Self(value))
+        } else {
+            None
         }
+    }
 
-        for bit in self.bits {
-            if bit {
-                f.write_char('1')?;
-            } else {
-                f.write_char('0')?;
-            }
+    /// The lowest 3 bits of `value`, like the machine's `% 8`.
+    pub const fn wrapping(value: u64) -> Self {
+        Self((value % 8) as u8)
+    }
+
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+}
+
+/// A number that isn't a [`U3`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum U3Error {
+    TooBig(u64),
+    NotANumber(String),
+}
+
+impl fmt::Display for U3Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            U3Error::TooBig(value) => write!(f, "Number doesn't fit in 3 bits: {value}"),
+            U3Error::NotANumber(text) => write!(f, "Not a 3-bit number: `{text}`"),
         }
+    }
+}
 
-        Ok(())
+impl std::error::Error for U3Error {}
+
+impl TryFrom<u8> for U3 {
+    type Error = U3Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::new(value).ok_or(U3Error::TooBig(value as u64))
     }
 }
 
-impl std::fmt::Display for Usize {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.value())
+impl FromStr for U3 {
+    type Err = U3Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = u64::from_str(s).map_err(|_| U3Error::NotANumber(s.to_string()))?;
+        u8::try_from(value)
+            .ok()
+            .and_then(Self::new)
+            .ok_or(U3Error::TooBig(value))
     }
 }
 
-impl Usize {
-    pub fn value(&self) -> u8 {
-        self.bits
-            .iter()
-            .enumerate()
-            .map(|(i, &bit)| (bit as u8) * (1 << i))
-            .sum()
+impl From<U3> for u8 {
+    fn from(value: U3) -> Self {
+        value.0
     }
+}
 
-    fn iter() -> impl Iterator<Item = Self> + Clone {
-        (0..8).map(|v| Usize::try_from(v).unwrap())
+impl From<U3> for u64 {
+    fn from(value: U3) -> Self {
+        value.0 as u64
     }
 }
 
-impl TryFrom<u8> for Usize {
-    type Error = eyre::Error;
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if value >= 1 << 3 {
-            eyre::bail!("Number doesn't fit in 3 bits: {value}");
-        }
+/// The value, which is also its only octal digit.
+impl fmt::Display for U3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
 
-        let bits: [bool; 3] = [0, 1, 2].map(|i| (value & (1 << i)) != 0);
+/// The 3 bits, like `0b101` (or the value with `{:#?}`).
+impl fmt::Debug for U3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "0b{:03b}", self.0)
+        }
+    }
+}
 
-        Ok(Self { bits })
+impl fmt::Binary for U3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Binary::fmt(&self.0, f)
     }
 }
 
-impl FromStr for Usize {
-    type Err = eyre::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(u8::from_str(s)?.try_into()?)
+impl fmt::Octal for U3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Octal::fmt(&self.0, f)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Register {
     A,
     B,
     C,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, FromRepr)]
+impl Register {
+    /// The register a combo operand reads, if it isn't a literal (or the
+    /// reserved 7).
+    fn from_combo(operand: U3) -> Option<Self> {
+        match operand.value() {
+            4 => Some(Self::A),
+            5 => Some(Self::B),
+            6 => Some(Self::C),
+            _ => None,
+        }
+    }
+}
+
+/// How an instruction uses its operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    Literal,
+    Combo,
+    Ignored,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, FromRepr, IntoStaticStr)]
 enum Instruction {
     #[strum(serialize = "adv")]
     DivisionA = 0,
@@ -107,43 +181,126 @@ enum Instruction {
 }
 
 impl Instruction {
-    pub fn execute(&self, operand: Usize, machine: &mut Machine) -> Option<Usize> {
+    /// The name of the instruction in assembly, like `adv`.
+    pub fn mnemonic(&self) -> &'static str {
+        self.into()
+    }
+
+    pub fn operand(&self) -> Operand {
         use Instruction as I;
 
-        // println!("reg_a: {:b}", machine.reg_a);
-        // println!("reg_b: {:b}", machine.reg_b);
-        // println!("reg_c: {:b}", machine.reg_c);
-        // println!("Running {self:?}({operand}), combo is {:b}", machine.combo(operand));
+        match self {
+            I::DivisionA | I::DivisionB | I::DivisionC | I::Modulo8 | I::Output => Operand::Combo,
+            I::BitwiseBXorOp | I::JumpNotZero => Operand::Literal,
+            I::BitwiseBXorC => Operand::Ignored,
+        }
+    }
+
+    /// Runs the instruction, moving the instruction pointer past it (or to
+    /// where it jumps).
+    pub fn execute(&self, operand: U3, machine: &mut Machine) -> Result<Option<U3>, MachineError> {
+        use Instruction as I;
 
+        let mut output = None;
         match self {
-            I::DivisionA => machine.reg_a >>= machine.combo(operand),
-            I::DivisionB => machine.reg_b = machine.reg_a >> machine.combo(operand),
-            I::DivisionC => machine.reg_c = machine.reg_a >> machine.combo(operand),
+            I::DivisionA => machine.reg_a = divide(machine.reg_a, machine.combo(operand)?),
+            I::DivisionB => machine.reg_b = divide(machine.reg_a, machine.combo(operand)?),
+            I::DivisionC => machine.reg_c = divide(machine.reg_a, machine.combo(operand)?),
 
-            I::BitwiseBXorOp => machine.reg_b ^= operand.value() as u64,
+            I::BitwiseBXorOp => machine.reg_b ^= u64::from(operand),
             I::BitwiseBXorC => machine.reg_b ^= machine.reg_c,
 
-            I::Modulo8 => machine.reg_b = machine.combo(operand) % 8,
+            I::Modulo8 => machine.reg_b = machine.combo(operand)? % 8,
             I::JumpNotZero => {
                 if machine.reg_a != 0 {
-                    machine.instruction_pointer = operand.value() as usize
+                    machine.instruction_pointer = operand.value() as usize;
+                    return Ok(None);
                 }
             }
-            I::Output => return Some(Usize::try_from((machine.combo(operand) % 8) as u8).unwrap()),
+            I::Output => output = Some(U3::wrapping(machine.combo(operand)?)),
         }
 
-        None
+        machine.instruction_pointer += 2;
+        Ok(output)
     }
 }
 
-impl From<Usize> for Instruction {
-    fn from(opcode: Usize) -> Self {
+impl From<U3> for Instruction {
+    fn from(opcode: U3) -> Self {
         Self::from_repr(opcode.value() as usize).unwrap()
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-struct Machine {
+/// `numerator / 2^shift`, which is 0 once the shift is past all the bits.
+fn divide(numerator: u64, shift: u64) -> u64 {
+    numerator.checked_shr(shift.try_into().unwrap_or(u32::MAX)).unwrap_or(0)
+}
+
+/// Splits `program` into its instructions and their operands, for the
+/// analyses that need whole instructions.
+fn instructions(program: &[U3]) -> eyre::Result<Vec<(Instruction, U3)>> {
+    eyre::ensure!(program.len() % 2 == 0, "The program has a value that isn't a whole instruction");
+
+    Ok(program
+        .chunks_exact(2)
+        .map(|chunk| (Instruction::from(chunk[0]), chunk[1]))
+        .collect())
+}
+
+/// A program the machine can't run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum MachineError {
+    ReservedCombo {
+        instruction_pointer: usize,
+    },
+    /// A jump to where there's no instruction to run.
+    JumpOutOfRange {
+        instruction_pointer: usize,
+        target: usize,
+    },
+    /// Ran for this many steps without halting, so it probably never does.
+    TooManySteps {
+        instruction_pointer: usize,
+        steps: usize,
+    },
+}
+
+impl fmt::Display for MachineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MachineError::ReservedCombo { instruction_pointer } => {
+                write!(f, "Combo operand 7 is reserved (at {instruction_pointer})")
+            }
+            MachineError::JumpOutOfRange {
+                instruction_pointer,
+                target,
+            } => write!(
+                f,
+                "The jump at {instruction_pointer} goes to {target}, outside of the program"
+            ),
+            MachineError::TooManySteps {
+                instruction_pointer,
+                steps,
+            } => write!(
+                f,
+                "The program didn't halt after {steps} steps (it's at {instruction_pointer})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MachineError {}
+
+/// What running a single instruction did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Status {
+    Halted,
+    Running,
+    Output(U3),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) struct Machine {
     reg_a: u64,
     reg_b: u64,
     reg_c: u64,
@@ -152,190 +309,185 @@ struct Machine {
 }
 
 impl Machine {
+    /// The outputs of `program`, until it halts or fails. Running for more
+    /// than [`MAX_STEPS`] counts as failing.
     #[must_use]
-    pub fn run<'a, 'b>(&'a mut self, program: &'b [Usize]) -> MachineIter<'a, 'b> {
+    pub fn run<'a, 'b>(&'a mut self, program: &'b [U3]) -> MachineIter<'a, 'b> {
         MachineIter {
             machine: self,
             program,
+            steps: 0,
+            failed: false,
         }
     }
 
-    pub fn combo(&self, operand: Usize) -> u64 {
+    /// Runs the instruction at the instruction pointer.
+    pub fn step(&mut self, program: &[U3]) -> Result<Status, MachineError> {
+        let instruction_pointer = self.instruction_pointer;
+        let (Some(&opcode), Some(&operand)) = (
+            program.get(instruction_pointer),
+            program.get(instruction_pointer + 1),
+        ) else {
+            return Ok(Status::Halted);
+        };
+
+        let instruction = Instruction::from(opcode);
+        let output = instruction.execute(operand, self)?;
+
+        let target = self.instruction_pointer;
+        if instruction == Instruction::JumpNotZero && self.reg_a != 0 && target + 1 >= program.len()
+        {
+            return Err(MachineError::JumpOutOfRange {
+                instruction_pointer,
+                target,
+            });
+        }
+
+        Ok(output.map_or(Status::Running, Status::Output))
+    }
+
+    /// The value of a combo operand, for the instruction at the instruction
+    /// pointer.
+    pub fn combo(&self, operand: U3) -> Result<u64, MachineError> {
         match operand.value() {
-            val @ 0..=3 => val as u64,
-            4 => self.reg_a,
-            5 => self.reg_b,
-            6 => self.reg_c,
-            7 => panic!("Invalid combo operand"),
-            _ => unreachable!(),
+            val @ 0..=3 => Ok(val as u64),
+            4 => Ok(self.reg_a),
+            5 => Ok(self.reg_b),
+            6 => Ok(self.reg_c),
+            _ => Err(MachineError::ReservedCombo {
+                instruction_pointer: self.instruction_pointer,
+            }),
         }
     }
 }
 
-struct MachineIter<'a, 'b> {
+pub(super) struct MachineIter<'a, 'b> {
     machine: &'a mut Machine,
-    program: &'b [Usize],
-}
-
-impl MachineIter<'_, '_> {
-    fn try_next(&mut self) -> Option<Option<Usize>> {
-        let opcode = *self.program.get(self.machine.instruction_pointer)?;
-        self.machine.instruction_pointer += 1;
-
-        let instruction = Instruction::from(opcode);
-
-        let operand = *self.program.get(self.machine.instruction_pointer)?;
-        self.machine.instruction_pointer += 1;
-
-        // println!(
-        //     "Running {instruction:?} with {operand} and reg_a={}",
-        //     self.machine.reg_a
-        // );
-
-        let output = instruction.execute(operand, &mut self.machine);
-        // dbg!(self.machine.instruction_pointer);
-
-        Some(output)
-    }
+    program: &'b [U3],
+    steps: usize,
+    failed: bool,
 }
 
 impl Iterator for MachineIter<'_, '_> {
-    type Item = Usize;
+    type Item = Result<U3, MachineError>;
     fn next(&mut self) -> Option<Self::Item> {
-        // println!("{:#?}", &self.machine);
-
-        // println!("reg_a: {:b}", self.machine.reg_a);
-        // println!("reg_b: {:b}", self.machine.reg_b);
-        // println!("reg_c: {:b}", self.machine.reg_c);
+        if self.failed {
+            return None;
+        }
 
         loop {
-            if let Some(output) = self.try_next()? {
-                return Some(output);
+            self.steps += 1;
+            let status = if self.steps > MAX_STEPS {
+                Err(MachineError::TooManySteps {
+                    instruction_pointer: self.machine.instruction_pointer,
+                    steps: MAX_STEPS,
+                })
+            } else {
+                self.machine.step(self.program)
+            };
+
+            match status {
+                Ok(Status::Halted) => return None,
+                Ok(Status::Running) => (),
+                Ok(Status::Output(output)) => return Some(Ok(output)),
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
             }
         }
     }
 }
 
-#[elvish::solution(day = 17)]
-fn part1(mut input: &str) -> String {
-    let (mut machine, program) = parse(&mut input).unwrap();
+/// Programs that run longer than this are taken to never halt.
+const MAX_STEPS: usize = 1 << 20;
 
-    let mut output = machine.run(&program);
+#[elvish::solution(day = 17)]
+fn part1(input: &str) -> Answer<String> {
+    Answer::new(|| {
+        let (mut machine, program) = parse(input)?;
 
-    output.join(",")
+        let outputs = machine.run(&program).collect::<Result<Vec<_>, _>>()?;
+        Ok(outputs.iter().join(","))
+    })
 }
 
-// 601201576113503: Too high
-
-#[elvish::solution(day = 17, example = 117440)]
-fn part2(mut input: &str) -> u64 {
-    let (initial_machine, program) = parse(&mut input).unwrap();
-    // println!("\n\n\n\n--- START ---\n\n\n\n");
-    //
-    // let initial_machine = Machine {
-    //     reg_b: 0,
-    //     ..initial_machine
-    // };
-    //
-    // // for reg_a in 0..(1 << 3 * 4) {
-    // for reg_a in [dbg!(0b101_010 + 0b000_000_000 + 0b101_000_000_000)] {
-    //     let mut machine = Machine {
-    //         reg_a,
-    //         ..initial_machine
-    //     };
-    //     let output = machine.run(&program).collect::<Vec<_>>();
-    //
-    //     dbg!(&program, &output);
-    //
-    //     if output.as_slice() == &program[0..3] {
-    //         return reg_a;
-    //     }
-    // }
-    //
-    // panic!();
-
-    // let mut queue = Usize::iter()
-    //     .flat_map(|bits| {
-    //         Usize::iter()
-    //             .flat_map(move |bits2| {
-    //                 Usize::iter().map(move |bits3| {
-    //                     let a = (bits.value() as u64) << 6;
-    //                     let b = (bits2.value() as u64) << 3;
-    //                     let c = (bits3.value() as u64) << 0;
-    //
-    //                     a + b + c
-    //                 })
-    //             })
-    //             .map(|reg_a| (reg_a, 0))
-    //     })
-    //     .collect::<VecDeque<_>>();
-    let mut queue = (0..0b111_111_111_111_111)
-        .map(|reg_a| (reg_a, 2))
-        .collect::<VecDeque<_>>();
-
-    let mut reg_a_output = Vec::new();
-    while let Some((reg_a, up_to)) = queue.pop_front() {
-        // dbg!(reg_a, up_to);
-        // if reg_a.checked_ilog2().is_some_and(|log| log / 3 - 1 != up_to as u32) {
-        //     println!("WARN");
-        //     dbg!(reg_a, up_to);
-        // };
-
-        let mut machine = Machine {
-            reg_a: reg_a
-                + 1u64
-                    .checked_shl(3 * (up_to + 3) as u32 + 2)
-                    .expect("not overflow"),
-            // reg_a,
-            ..initial_machine
-        };
+/// Writes the program as assembly to `day17.asm` in `dir`, ending with the
+/// formulas of its loop if it's a simple one (and whether they agree with
+/// running it), and every step of the run to `day17.trace`.
+///
+/// If `dir` has a `day17-program.asm` (in the same syntax), that's traced
+/// instead of the input's program. A `day17-breakpoints` file (a breakpoint
+/// like `ip 4` or `out 3` per line) marks where they're hit in the trace.
+pub fn inspect(input: &str, dir: &Path) -> eyre::Result<()> {
+    let (machine, mut program) = parse(input)?;
+
+    let mut assembly = asm::disassemble(&program);
+    if let Ok(iteration) = symbolic::lift(&program) {
+        if let Some(formulas) = iteration.formulas() {
+            assembly.extend(formulas.lines().map(|formula| format!("\n; {formula}")));
+
+            // Double check them against actually running it.
+            let mut debugger = Debugger::new(machine, &program).with_max_steps(MAX_STEPS);
+            let checked = debugger.run().is_ok() && iteration.matches(&machine, &debugger.output);
+            assembly += if checked {
+                "\n; (they give the same output as running it)\n"
+            } else {
+                "\n; (they DON'T give the same output as running it)\n"
+            };
+        }
+    }
+    fs::write(dir.join("day17.asm"), assembly)?;
 
-        let output = machine.run(&program).take(up_to).collect::<Vec<_>>();
+    let custom = dir.join("day17-program.asm");
+    if custom.exists() {
+        program = asm::assemble(&fs::read_to_string(custom)?)?;
+    }
 
-        if output.len() < up_to {
-            panic!();
-        }
-        if output == program {
-            println!("CANDIDATE! {reg_a}");
-            reg_a_output.push(reg_a);
-            continue;
+    let mut debugger = Debugger::new(machine, &program).with_max_steps(MAX_STEPS).recording();
+    let breakpoints = dir.join("day17-breakpoints");
+    if breakpoints.exists() {
+        for line in fs::read_to_string(breakpoints)?.lines() {
+            if !line.trim().is_empty() {
+                debugger = debugger.with_breakpoint(line.parse()?);
+            }
         }
+    }
 
-        if output.as_slice() != &program[0..up_to] {
-            continue;
+    // Where each breakpoint was hit, as the number of steps before it.
+    let mut hits = Vec::new();
+    let result = loop {
+        match debugger.run() {
+            Ok(Stop::Halted) => break Ok(()),
+            Ok(Stop::Breakpoint(breakpoint)) => hits.push((debugger.trace.len(), breakpoint)),
+            Err(error) => break Err(error),
         }
+    };
 
-        println!("{reg_a} produces input up to {up_to}. {output:?}, {program:?}");
-
-        if up_to > 14 {
-            for next_bits in 0u64..0b111 {
-                let shift = 3 * (up_to + 4);
-                let reg_a_next = reg_a + (next_bits.checked_shl(shift as u32).unwrap());
-                // dbg!(reg_a_next);
-                queue.push_back((reg_a_next, up_to + 1));
-            }
-            continue;
+    let mut trace = String::new();
+    let mut hits = hits.into_iter().peekable();
+    for (i, step) in debugger.trace.iter().enumerate() {
+        while let Some((_, breakpoint)) = hits.next_if(|&(at, _)| at == i) {
+            trace += &format!("; hit {breakpoint}\n");
         }
 
-        for next_bits in 0u64..0b111_111 {
-            let shift = 3 * (up_to + 4);
-            let reg_a_next = reg_a + (next_bits.checked_shl(shift as u32).unwrap());
-            // dbg!(reg_a_next);
-            queue.push_back((reg_a_next, up_to + 2));
-        }
+        trace += &format!("{step}\n");
     }
+    for (_, breakpoint) in hits {
+        trace += &format!("; hit {breakpoint}\n");
+    }
+    fs::write(dir.join("day17.trace"), trace)?;
 
-    reg_a_output.into_iter().filter(|&reg_a| {
-        dbg!(reg_a);
-
-        let mut machine = Machine {
-            reg_a,
-            ..initial_machine
-        };
+    result
+}
 
-        let output = machine.run(&program).collect::<Vec<_>>();
-        output == program
-    }).min().unwrap()
+/// See [`quine`].
+#[elvish::solution(day = 17, example = 117440)]
+fn part2(input: &str) -> Answer<u64> {
+    Answer::new(|| {
+        let (machine, program) = parse(input)?;
+        quine::smallest(machine, &program)
+    })
 }
 
 elvish::example!(
@@ -358,10 +510,71 @@ elvish::example!(
 
 #[test]
 fn example_part1() {
-    let solution = part1(EXAMPLE_PART1);
+    let solution = part1(EXAMPLE_PART1).into_result().unwrap();
     assert_eq!(solution.as_str(), "4,6,3,5,6,3,5,2,1,0")
 }
 
+#[test]
+fn endless_loop() {
+    // Jumps back to the start forever, without ever outputting anything.
+    let program = [3, 0].map(|i| i.try_into().unwrap());
+    let mut machine = Machine {
+        reg_a: 1,
+        ..Default::default()
+    };
+
+    let outputs = machine.run(&program).collect::<Vec<_>>();
+    let error = MachineError::TooManySteps {
+        instruction_pointer: 0,
+        steps: MAX_STEPS,
+    };
+    assert_eq!(outputs, [Err(error)]);
+}
+
+#[test]
+fn inspecting() {
+    let dir = std::env::temp_dir().join(format!("aoc-day17-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    inspect(EXAMPLE_PART1, &dir).unwrap();
+    let assembly = fs::read_to_string(dir.join("day17.asm")).unwrap();
+    assert!(assembly.contains("; (they give the same output as running it)"), "{assembly}");
+    assert_eq!(
+        asm::assemble(&assembly).unwrap(),
+        parse(EXAMPLE_PART1).unwrap().1
+    );
+    assert!(
+        fs::read_to_string(dir.join("day17.trace"))
+            .unwrap()
+            .lines()
+            .count()
+            > 10
+    );
+
+    // Outputs A % 8 once, instead of looping.
+    fs::write(dir.join("day17-program.asm"), "out A").unwrap();
+    inspect(EXAMPLE_PART1, &dir).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("day17.trace")).unwrap().lines().count(), 1);
+
+    fs::write(dir.join("day17-breakpoints"), "ip 0\nout 1\n").unwrap();
+    inspect(EXAMPLE_PART1, &dir).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("day17.trace")).unwrap().lines().collect::<Vec<_>>(),
+        [
+            "; hit ip 0",
+            "  0: out 4 (combo 729) | A=729 B=0 C=0 -> A=729 B=0 C=0 | out 1",
+            "; hit out 1",
+        ]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn example_part2() {
+    assert_eq!(part2(EXAMPLE_PART2).into_result().unwrap(), 117440);
+}
+
 #[test]
 fn example_instructions() {
     // If register C contains 9, the program 2,6 would set register B to 1.
@@ -381,6 +594,7 @@ fn example_instructions() {
         ..Default::default()
     }
     .run(&[5, 0, 5, 1, 5, 4].map(|i| i.try_into().unwrap()))
+    .map(Result::unwrap)
     .join(",");
 
     assert_eq!(output.as_str(), "0,1,2");
@@ -391,6 +605,7 @@ fn example_instructions() {
         ..Default::default()
     }
     .run(&[0, 1, 5, 4, 3, 0].map(|i| i.try_into().unwrap()))
+    .map(Result::unwrap)
     .join(",");
 
     assert_eq!(output.as_str(), "4,2,5,6,7,7,7,7,3,1,0");
@@ -418,23 +633,71 @@ fn example_instructions() {
     assert_eq!(machine.reg_b, 44354);
 }
 
-fn parse(input: &mut &str) -> PResult<(Machine, Vec<Usize>)> {
-    let mut parser = winnow::seq!(Machine {
-        reg_a:  preceded("Register A: ", digit1.parse_to()),
-        _: multispace0,
-        reg_b:  preceded("Register B: ", digit1.parse_to()),
-        _: multispace0,
-        reg_c:  preceded("Register C: ", digit1.parse_to()),
-        _: multispace0,
+#[test]
+fn machine_errors() {
+    let program = |values: &[u8]| {
+        values
+            .iter()
+            .map(|&value| U3::try_from(value).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let mut machine = Machine::default();
+    let outputs = machine
+        .run(&program(&[1, 3, 5, 7, 5, 4]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outputs,
+        [Err(MachineError::ReservedCombo {
+            instruction_pointer: 2
+        })]
+    );
+
+    let mut machine = Machine {
+        reg_a: 1,
         ..Default::default()
-    });
+    };
+    let outputs = machine.run(&program(&[5, 4, 3, 5])).collect::<Vec<_>>();
+    let error = MachineError::JumpOutOfRange {
+        instruction_pointer: 2,
+        target: 5,
+    };
+    assert_eq!(outputs, [Ok(U3::new(1).unwrap()), Err(error)]);
+
+    assert_eq!("7".parse::<U3>(), Ok(U3::new(7).unwrap()));
+    assert_eq!("8".parse::<U3>(), Err(U3Error::TooBig(8)));
+    assert_eq!("x".parse::<U3>(), Err(U3Error::NotANumber("x".to_string())));
+    assert_eq!(U3::wrapping(13), U3::new(5).unwrap());
+    assert_eq!(
+        format!(
+            "{:?} {:#?} {:o} {}",
+            U3::new(5).unwrap(),
+            U3::new(5).unwrap(),
+            U3::new(7).unwrap(),
+            U3::ZERO
+        ),
+        "0b101 5 7 0"
+    );
+}
 
-    let machine = parser.parse_next(input)?;
-    let program = preceded(
-        "Program: ",
-        separated(0.., digit1.parse_to::<Usize>(), ',').map(|l: Vec<_>| l),
-    )
+pub(super) fn parse(input: &str) -> Result<(Machine, Vec<U3>), ParseError> {
+    parse::all(input, machine_and_program)
+}
+
+fn machine_and_program(input: &mut &str) -> PResult<(Machine, Vec<U3>)> {
+    let machine = winnow::seq!(Machine {
+        reg_a: parse::field("Register A", uint),
+        _: line_ending,
+        reg_b: parse::field("Register B", uint),
+        _: line_ending,
+        reg_c: parse::field("Register C", uint),
+        _: parse::blank_line,
+        ..Default::default()
+    })
     .parse_next(input)?;
 
+    let instruction = digit1.parse_to::<U3>().context(StrContext::Label("3-bit number"));
+    let program = parse::field("Program", parse::comma_list(instruction)).parse_next(input)?;
+
     Ok((machine, program))
 }
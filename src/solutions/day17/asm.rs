@@ -0,0 +1,253 @@
+//! An assembler and disassembler for the 3-bit machine, to read what the
+//! programs actually do.
+//!
+//! The assembly has an instruction per line, like `bst A` or `jnz L0`, with
+//! combo operands written as `0`-`3`, `A`, `B` or `C`. Jump targets get a label
+//! (`L0:`) and everything after a `;` is a comment. Leftover values that aren't
+//! a whole instruction are written as `.raw 3`.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write as _,
+    str::FromStr as _,
+};
+
+use winnow::{
+    ascii::{alpha1, space0, space1},
+    combinator::{alt, cut_err, opt, peek, preceded, separated, terminated},
+    error::StrContext,
+    token::{one_of, take_till, take_while},
+    PResult, Parser as _,
+};
+
+use super::{Instruction, Operand, Register, U3};
+use crate::parse::{self, ParseError};
+
+fn label(address: usize) -> String {
+    format!("L{address}")
+}
+
+/// The addresses that jumps land on, if they're the start of an instruction.
+fn jump_targets(program: &[U3]) -> BTreeSet<usize> {
+    program
+        .chunks_exact(2)
+        .filter(|chunk| Instruction::from(chunk[0]) == Instruction::JumpNotZero)
+        .map(|chunk| chunk[1].value() as usize)
+        .filter(|&target| target % 2 == 0 && target < program.len())
+        .collect()
+}
+
+/// An instruction with its operand resolved, and a comment with what it does.
+fn instruction(instruction: Instruction, operand: U3, targets: &BTreeSet<usize>) -> String {
+    use Instruction as I;
+
+    let text = match instruction.operand() {
+        Operand::Combo => match Register::from_combo(operand) {
+            Some(register) => format!("{register:?}"),
+            None => operand.to_string(),
+        },
+        Operand::Literal
+            if instruction == I::JumpNotZero && targets.contains(&(operand.value() as usize)) =>
+        {
+            label(operand.value() as usize)
+        }
+        Operand::Literal => operand.to_string(),
+        Operand::Ignored if operand.value() == 0 => String::new(),
+        Operand::Ignored => operand.to_string(),
+    };
+
+    let comment = match instruction {
+        _ if instruction.operand() == Operand::Combo && operand.value() == 7 => {
+            "combo operand 7 is reserved".to_string()
+        }
+        I::DivisionA => format!("A = A >> {text}"),
+        I::DivisionB => format!("B = A >> {text}"),
+        I::DivisionC => format!("C = A >> {text}"),
+        I::BitwiseBXorOp => format!("B = B ^ {text}"),
+        I::BitwiseBXorC => "B = B ^ C".to_string(),
+        I::Modulo8 => format!("B = {text} % 8"),
+        I::JumpNotZero => format!("if A != 0, jump to {text}"),
+        I::Output => format!("output {text} % 8"),
+    };
+
+    let code = format!("{} {text}", instruction.mnemonic());
+    format!("{:<12}; {comment}", code.trim_end())
+}
+
+/// The program as assembly, with a line per instruction.
+pub fn disassemble(program: &[U3]) -> String {
+    let targets = jump_targets(program);
+
+    let mut out = String::new();
+    for (address, chunk) in (0..).step_by(2).zip(program.chunks(2)) {
+        if targets.contains(&address) {
+            writeln!(out, "{}:", label(address)).unwrap();
+        }
+
+        let line = match *chunk {
+            [opcode, operand] => instruction(Instruction::from(opcode), operand, &targets),
+            [value] => format!("{:<12}; no room for an operand", format!(".raw {value}")),
+            _ => unreachable!(),
+        };
+
+        writeln!(out, "    {line}").unwrap();
+    }
+
+    out
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Word<'i> {
+    Value(U3),
+    Label(&'i str),
+}
+
+#[derive(Debug)]
+struct Line<'i> {
+    label: Option<&'i str>,
+    words: Vec<Word<'i>>,
+}
+
+fn name<'i>(input: &mut &'i str) -> PResult<&'i str> {
+    (
+        one_of(|char: char| char.is_ascii_alphabetic() || char == '_'),
+        take_while(0.., |char: char| char.is_ascii_alphanumeric() || char == '_'),
+    )
+        .take()
+        .parse_next(input)
+}
+
+fn value(input: &mut &str) -> PResult<U3> {
+    one_of('0'..='7')
+        .map(|digit: char| U3::new(digit as u8 - b'0').unwrap())
+        .context(StrContext::Label("3-bit number"))
+        .parse_next(input)
+}
+
+fn combo(input: &mut &str) -> PResult<U3> {
+    let register =
+        one_of(['A', 'B', 'C']).map(|register: char| U3::new(register as u8 - b'A' + 4).unwrap());
+
+    alt((value, register))
+        .context(StrContext::Label("combo operand"))
+        .parse_next(input)
+}
+
+fn operation<'i>(input: &mut &'i str) -> PResult<Vec<Word<'i>>> {
+    let instruction = alpha1
+        .verify_map(|mnemonic| Instruction::from_str(mnemonic).ok())
+        .context(StrContext::Label("instruction"));
+    let instruction = preceded(peek(alpha1), cut_err(instruction)).parse_next(input)?;
+
+    let operand = match instruction.operand() {
+        Operand::Combo => cut_err(preceded(space1, combo))
+            .map(Word::Value)
+            .parse_next(input)?,
+        Operand::Literal if instruction == Instruction::JumpNotZero => {
+            let target = alt((value.map(Word::Value), name.map(Word::Label)))
+                .context(StrContext::Label("jump target"));
+            cut_err(preceded(space1, target)).parse_next(input)?
+        }
+        Operand::Literal => cut_err(preceded(space1, value))
+            .map(Word::Value)
+            .parse_next(input)?,
+        Operand::Ignored => {
+            let operand = opt(preceded(space1, value)).parse_next(input)?;
+            Word::Value(operand.unwrap_or(U3::ZERO))
+        }
+    };
+
+    let opcode = U3::new(instruction as u8).unwrap();
+    Ok(vec![Word::Value(opcode), operand])
+}
+
+fn raw<'i>(input: &mut &'i str) -> PResult<Vec<Word<'i>>> {
+    preceded(
+        (".raw", space1),
+        cut_err(separated(1.., value.map(Word::Value), space1)),
+    )
+    .parse_next(input)
+}
+
+fn line<'i>(input: &mut &'i str) -> PResult<Line<'i>> {
+    space0.parse_next(input)?;
+    let label = opt(terminated(name, (':', space0))).parse_next(input)?;
+    let words = opt(alt((raw, operation))).parse_next(input)?.unwrap_or_default();
+    (space0, opt((';', take_till(0.., ['\r', '\n'])))).parse_next(input)?;
+
+    Ok(Line { label, words })
+}
+
+/// Parses assembly (like the one [`disassemble`] writes) back into a program.
+pub fn assemble(source: &str) -> Result<Vec<U3>, ParseError> {
+    let lines = parse::all(source, parse::lines(line))?;
+
+    let mut labels = HashMap::new();
+    let mut address = 0;
+    for line in &lines {
+        if let Some(label) = line.label {
+            if labels.insert(label, address).is_some() {
+                return Err(ParseError::at(
+                    source,
+                    label,
+                    format!("Label `{label}` is defined twice"),
+                ));
+            }
+        }
+
+        address += line.words.len();
+    }
+
+    lines
+        .iter()
+        .flat_map(|line| &line.words)
+        .map(|&word| match word {
+            Word::Value(value) => Ok(value),
+            Word::Label(label) => {
+                let &address = labels.get(label).ok_or_else(|| {
+                    ParseError::at(source, label, format!("Unknown label `{label}`"))
+                })?;
+
+                u8::try_from(address).ok().and_then(U3::new).ok_or_else(|| {
+                    ParseError::at(
+                        source,
+                        label,
+                        format!("Label `{label}` is at {address}, too far to jump to"),
+                    )
+                })
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    let programs = [
+        "0,1,5,4,3,0",
+        "0,3,5,4,3,0",
+        "2,6",
+        "5,0,5,1,5,4",
+        "1,7",
+        "4,0",
+        "2,4,1,1,7,5,4,4,1,4,0,3,5,5,3,0",
+        "3,3,0,7,5,3",
+        "4,3,1",
+    ];
+
+    for program in programs {
+        let program = program.split(',').map(|value| value.parse().unwrap()).collect::<Vec<U3>>();
+        let assembly = disassemble(&program);
+        assert_eq!(assemble(&assembly).unwrap(), program, "{assembly}");
+    }
+
+    let program = [0, 3, 5, 4, 3, 0].map(|value| U3::try_from(value).unwrap());
+    assert_eq!(
+        disassemble(&program),
+        "L0:\n    adv 3       ; A = A >> 3\n    out A       ; output A % 8\n    jnz L0      ; if A != 0, jump to L0\n",
+    );
+
+    let error = assemble("loop:\n    adv 3\n    jnz start").unwrap_err();
+    assert_eq!((error.line, error.column), (3, 9));
+    let error = assemble("    adv 9").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 9));
+}
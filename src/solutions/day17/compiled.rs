@@ -0,0 +1,353 @@
+//! The program decoded once up front, for when it has to run a lot of times
+//! (like when searching for A). Registers are plain `u64`s and every address
+//! is already an operation with its operand picked, so a step is a single
+//! `match`.
+
+use color_eyre::eyre;
+use rayon::iter::{IntoParallelIterator, ParallelIterator as _};
+
+use super::{divide, Instruction, Machine, MachineError, MAX_STEPS, U3};
+
+/// An instruction with its combo operand already picked, so running it doesn't
+/// have to look at the operand again. `Lit` is a literal operand and `A`, `B`
+/// and `C` read that register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    AdvLit(u32),
+    AdvA,
+    AdvB,
+    AdvC,
+    BdvLit(u32),
+    BdvA,
+    BdvB,
+    BdvC,
+    CdvLit(u32),
+    CdvA,
+    CdvB,
+    CdvC,
+    Bxl(u64),
+    BstLit(u64),
+    BstA,
+    BstB,
+    BstC,
+    Jnz(usize),
+    Bxc,
+    OutLit(u8),
+    OutA,
+    OutB,
+    OutC,
+    /// An instruction with combo operand 7, which is only an error if it runs.
+    Reserved,
+}
+
+impl Op {
+    fn new(instruction: Instruction, operand: U3) -> Self {
+        use Instruction as I;
+
+        let literal = operand.value();
+        match (instruction, literal) {
+            (I::BitwiseBXorOp, _) => Op::Bxl(literal.into()),
+            (I::JumpNotZero, _) => Op::Jnz(literal.into()),
+            (I::BitwiseBXorC, _) => Op::Bxc,
+            (_, 7) => Op::Reserved,
+
+            (I::DivisionA, 0..=3) => Op::AdvLit(literal.into()),
+            (I::DivisionA, 4) => Op::AdvA,
+            (I::DivisionA, 5) => Op::AdvB,
+            (I::DivisionA, _) => Op::AdvC,
+
+            (I::DivisionB, 0..=3) => Op::BdvLit(literal.into()),
+            (I::DivisionB, 4) => Op::BdvA,
+            (I::DivisionB, 5) => Op::BdvB,
+            (I::DivisionB, _) => Op::BdvC,
+
+            (I::DivisionC, 0..=3) => Op::CdvLit(literal.into()),
+            (I::DivisionC, 4) => Op::CdvA,
+            (I::DivisionC, 5) => Op::CdvB,
+            (I::DivisionC, _) => Op::CdvC,
+
+            (I::Modulo8, 0..=3) => Op::BstLit(literal.into()),
+            (I::Modulo8, 4) => Op::BstA,
+            (I::Modulo8, 5) => Op::BstB,
+            (I::Modulo8, _) => Op::BstC,
+
+            (I::Output, 0..=3) => Op::OutLit(literal),
+            (I::Output, 4) => Op::OutA,
+            (I::Output, 5) => Op::OutB,
+            (I::Output, _) => Op::OutC,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Compiled {
+    /// The operation starting at each address, since jumps can land on odd
+    /// ones too.
+    ops: Vec<Op>,
+}
+
+impl Compiled {
+    pub fn new(program: &[U3]) -> Self {
+        let ops = program
+            .windows(2)
+            .map(|window| Op::new(Instruction::from(window[0]), window[1]))
+            .collect();
+
+        Self { ops }
+    }
+
+    /// Runs the program, passing each output to `output` until it returns
+    /// `false`.
+    fn execute(&self, machine: Machine, mut output: impl FnMut(u8) -> bool) -> eyre::Result<()> {
+        let Machine {
+            mut reg_a,
+            mut reg_b,
+            mut reg_c,
+            instruction_pointer: mut ip,
+        } = machine;
+
+        for _ in 0..MAX_STEPS {
+            let Some(&op) = self.ops.get(ip) else {
+                return Ok(());
+            };
+            ip += 2;
+
+            match op {
+                // Literal shifts are at most 3, so they can't overflow.
+                Op::AdvLit(shift) => reg_a >>= shift,
+                Op::AdvA => reg_a = divide(reg_a, reg_a),
+                Op::AdvB => reg_a = divide(reg_a, reg_b),
+                Op::AdvC => reg_a = divide(reg_a, reg_c),
+                Op::BdvLit(shift) => reg_b = reg_a >> shift,
+                Op::BdvA => reg_b = divide(reg_a, reg_a),
+                Op::BdvB => reg_b = divide(reg_a, reg_b),
+                Op::BdvC => reg_b = divide(reg_a, reg_c),
+                Op::CdvLit(shift) => reg_c = reg_a >> shift,
+                Op::CdvA => reg_c = divide(reg_a, reg_a),
+                Op::CdvB => reg_c = divide(reg_a, reg_b),
+                Op::CdvC => reg_c = divide(reg_a, reg_c),
+                Op::Bxl(literal) => reg_b ^= literal,
+                Op::BstLit(literal) => reg_b = literal,
+                Op::BstA => reg_b = reg_a % 8,
+                Op::BstB => reg_b %= 8,
+                Op::BstC => reg_b = reg_c % 8,
+                Op::Jnz(target) => {
+                    if reg_a != 0 {
+                        // There's an op at every address with an instruction.
+                        if target >= self.ops.len() {
+                            let instruction_pointer = ip - 2;
+                            return Err(MachineError::JumpOutOfRange {
+                                instruction_pointer,
+                                target,
+                            }
+                            .into());
+                        }
+
+                        ip = target;
+                    }
+                }
+                Op::Bxc => reg_b ^= reg_c,
+                Op::OutLit(literal) => {
+                    if !output(literal) {
+                        return Ok(());
+                    }
+                }
+                Op::OutA => {
+                    if !output((reg_a % 8) as u8) {
+                        return Ok(());
+                    }
+                }
+                Op::OutB => {
+                    if !output((reg_b % 8) as u8) {
+                        return Ok(());
+                    }
+                }
+                Op::OutC => {
+                    if !output((reg_c % 8) as u8) {
+                        return Ok(());
+                    }
+                }
+                Op::Reserved => {
+                    let instruction_pointer = ip - 2;
+                    return Err(MachineError::ReservedCombo { instruction_pointer }.into());
+                }
+            }
+        }
+
+        Err(MachineError::TooManySteps {
+            instruction_pointer: ip,
+            steps: MAX_STEPS,
+        }
+        .into())
+    }
+
+    /// The first `limit` values the program outputs (or all of them, if it
+    /// halts before).
+    pub fn run(&self, machine: Machine, limit: usize) -> eyre::Result<Vec<u8>> {
+        let mut outputs = Vec::new();
+        if limit > 0 {
+            self.execute(machine, |output| {
+                outputs.push(output);
+                outputs.len() < limit
+            })?;
+        }
+
+        Ok(outputs)
+    }
+
+    /// Whether the program outputs exactly `expected`, stopping as soon as it
+    /// doesn't.
+    pub fn outputs(&self, machine: Machine, expected: &[U3]) -> eyre::Result<bool> {
+        let mut rest = expected.iter();
+        let mut matches = true;
+        self.execute(machine, |output| {
+            matches = rest.next().is_some_and(|value| value.value() == output);
+            matches
+        })?;
+
+        Ok(matches && rest.next().is_none())
+    }
+
+    /// [`Compiled::run`] for every value of A in `reg_as`, in parallel.
+    pub fn run_many(
+        &self,
+        machine: Machine,
+        reg_as: impl IntoParallelIterator<Item = u64>,
+        limit: usize,
+    ) -> Vec<(u64, eyre::Result<Vec<u8>>)> {
+        reg_as
+            .into_par_iter()
+            .map(|reg_a| (reg_a, self.run(Machine { reg_a, ..machine }, limit)))
+            .collect()
+    }
+
+    /// The smallest value of A in `reg_as` for which the program outputs
+    /// exactly `expected`, by brute force in parallel. Errors count as not
+    /// matching.
+    pub fn find_first(
+        &self,
+        machine: Machine,
+        reg_as: impl IntoParallelIterator<Item = u64>,
+        expected: &[U3],
+    ) -> Option<u64> {
+        reg_as.into_par_iter().find_first(|&reg_a| {
+            self.outputs(Machine { reg_a, ..machine }, expected)
+                .unwrap_or(false)
+        })
+    }
+}
+
+#[test]
+fn same_as_the_machine() {
+    let programs = [
+        "0,1,5,4,3,0",
+        "0,3,5,4,3,0",
+        "2,4,1,1,7,5,4,4,1,4,0,3,5,5,3,0",
+        "2,4,1,2,7,5,4,1,1,3,5,5,0,3,3,0",
+        // Jumps to the odd address 1, running `bst 5` and then `out B`.
+        "3,2,5,5,3,1",
+    ];
+
+    for program in programs {
+        let program = program.split(',').map(|value| value.parse().unwrap()).collect::<Vec<U3>>();
+        let compiled = Compiled::new(&program);
+
+        for reg_a in [0, 1, 7, 729, 2024, 117440, 202991746427434] {
+            let machine = Machine {
+                reg_a,
+                reg_b: 5,
+                ..Default::default()
+            };
+            let mut reference = machine;
+            let expected = reference
+                .run(&program)
+                .take(100)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            let outputs = compiled.run(machine, 100).unwrap();
+            assert!(
+                outputs
+                    .iter()
+                    .copied()
+                    .eq(expected.iter().copied().map(U3::value)),
+                "{program:?} with {reg_a}"
+            );
+            assert!(compiled.outputs(machine, &expected).unwrap() || expected.len() == 100);
+        }
+    }
+
+    let program = [0, 3, 5, 4, 3, 0].map(|value| U3::try_from(value).unwrap());
+    let compiled = Compiled::new(&program);
+    assert_eq!(
+        compiled.find_first(Machine::default(), 0..200_000, &program),
+        Some(117440)
+    );
+
+    let results = compiled.run_many(Machine::default(), vec![8, 16], 1);
+    assert_eq!(
+        results
+            .into_iter()
+            .map(|(_, output)| output.unwrap())
+            .collect::<Vec<_>>(),
+        [vec![1], vec![2]]
+    );
+
+    // Jumping back to the start with A never reaching 0.
+    let compiled = Compiled::new(&[3, 0].map(|value| U3::try_from(value).unwrap()));
+    let machine = Machine {
+        reg_a: 1,
+        ..Default::default()
+    };
+    let error = compiled.run(machine, 1).unwrap_err();
+    assert!(
+        matches!(
+            error.downcast_ref::<MachineError>(),
+            Some(MachineError::TooManySteps { .. })
+        ),
+        "{error}"
+    );
+}
+
+/// A brute-force search for the quine over a million values of A (none of
+/// which match), with the machine one at a time and with [`Compiled::find_first`].
+/// Decoding up front only makes each thread about twice as fast (most values
+/// stop matching after the first output, and what's left is mostly the
+/// dispatch on each op), so it's only 10x faster with enough cores. Run with
+/// `cargo test --release speedup -- --ignored --nocapture`, since the timings
+/// don't mean much without optimizations.
+#[test]
+#[ignore]
+fn speedup() {
+    use std::time::Instant;
+
+    let program = "2,4,1,1,7,5,4,4,1,4,0,3,5,5,3,0";
+    let program = program.split(',').map(|value| value.parse().unwrap()).collect::<Vec<U3>>();
+    let candidates = 0..1_000_000;
+
+    let start = Instant::now();
+    let interpreted = candidates.clone().find(|&reg_a| {
+        let mut machine = Machine {
+            reg_a,
+            ..Default::default()
+        };
+        machine
+            .run(&program)
+            .map(Result::unwrap)
+            .eq(program.iter().copied())
+    });
+    let interpreted_time = start.elapsed();
+
+    let start = Instant::now();
+    let compiled = Compiled::new(&program).find_first(Machine::default(), candidates, &program);
+    let compiled_time = start.elapsed();
+
+    let speedup = interpreted_time.as_secs_f64() / compiled_time.as_secs_f64();
+    println!(
+        "interpreted: {interpreted_time:?}, compiled on {} threads: {compiled_time:?} ({speedup:.1}x)",
+        rayon::current_num_threads()
+    );
+
+    assert_eq!(interpreted, compiled);
+    assert!(speedup > 1.0, "the compiled search is slower ({speedup:.1}x)");
+}
@@ -0,0 +1,313 @@
+//! Runs the machine one instruction at a time, recording what each step did.
+//! Stops at breakpoints, and gives up on programs that don't halt.
+
+use std::{fmt, str::FromStr};
+
+use color_eyre::eyre::{self, WrapErr as _};
+
+use super::{asm, Instruction, Machine, MachineError, Operand, Status, U3};
+
+/// What a single instruction did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub instruction_pointer: usize,
+    pub instruction: Instruction,
+    pub operand: U3,
+    /// The value of the operand, if it's a combo operand.
+    pub combo: Option<u64>,
+    pub before: Machine,
+    pub after: Machine,
+    pub output: Option<U3>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { before, after, .. } = self;
+
+        write!(
+            f,
+            "{:>3}: {} {}",
+            self.instruction_pointer,
+            self.instruction.mnemonic(),
+            self.operand
+        )?;
+        if let Some(combo) = self.combo {
+            write!(f, " (combo {combo})")?;
+        }
+
+        write!(
+            f,
+            " | A={} B={} C={} -> A={} B={} C={}",
+            before.reg_a, before.reg_b, before.reg_c, after.reg_a, after.reg_b, after.reg_c
+        )?;
+
+        if let Some(output) = self.output {
+            write!(f, " | out {output}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Before running the instruction at this address.
+    InstructionPointer(usize),
+    /// After the program has output this many values.
+    Outputs(usize),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InstructionPointer(address) => write!(f, "ip {address}"),
+            Self::Outputs(count) => write!(f, "out {count}"),
+        }
+    }
+}
+
+/// The same as it's displayed, `ip 4` or `out 3`.
+impl FromStr for Breakpoint {
+    type Err = eyre::Report;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((kind, value)) = s.trim().split_once(' ') else {
+            eyre::bail!("Expected `ip N` or `out N`, got `{s}`");
+        };
+
+        let value = value.trim().parse()?;
+        match kind {
+            "ip" => Ok(Self::InstructionPointer(value)),
+            "out" => Ok(Self::Outputs(value)),
+            _ => eyre::bail!("Unknown breakpoint `{kind}`, expected `ip` or `out`"),
+        }
+    }
+}
+
+/// Why [`Debugger::run`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    Breakpoint(Breakpoint),
+}
+
+pub struct Debugger<'p> {
+    pub machine: Machine,
+    program: &'p [U3],
+
+    breakpoints: Vec<Breakpoint>,
+    max_steps: Option<usize>,
+    recording: bool,
+
+    steps: usize,
+    /// Where the last run stopped at a breakpoint, so running again doesn't
+    /// stop there right away.
+    stopped_at: Option<usize>,
+    pub output: Vec<U3>,
+    pub trace: Vec<Step>,
+}
+
+impl<'p> Debugger<'p> {
+    pub fn new(machine: Machine, program: &'p [U3]) -> Self {
+        Self {
+            machine,
+            program,
+            breakpoints: Vec::new(),
+            max_steps: None,
+            recording: false,
+            steps: 0,
+            stopped_at: None,
+            output: Vec::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn with_breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+        self.breakpoints.push(breakpoint);
+        self
+    }
+
+    /// Makes [`Debugger::run`] fail once the machine has run `max_steps`
+    /// instructions in total without halting.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Keeps every step in [`Debugger::trace`].
+    pub fn recording(mut self) -> Self {
+        self.recording = true;
+        self
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.machine.instruction_pointer + 1 >= self.program.len()
+    }
+
+    /// Runs the next instruction, or returns `None` if the machine has halted.
+    pub fn step(&mut self) -> Result<Option<Step>, MachineError> {
+        if self.is_halted() {
+            return Ok(None);
+        }
+
+        self.stopped_at = None;
+        let before = self.machine;
+        let instruction_pointer = before.instruction_pointer;
+        let instruction = Instruction::from(self.program[instruction_pointer]);
+        let operand = self.program[instruction_pointer + 1];
+
+        let combo = (instruction.operand() == Operand::Combo)
+            .then(|| self.machine.combo(operand))
+            .transpose()?;
+
+        let output = match self.machine.step(self.program)? {
+            Status::Output(output) => Some(output),
+            Status::Running | Status::Halted => None,
+        };
+
+        let step = Step {
+            instruction_pointer,
+            instruction,
+            operand,
+            combo,
+            before,
+            after: self.machine,
+            output,
+        };
+
+        self.steps += 1;
+        self.output.extend(output);
+        if self.recording {
+            self.trace.push(step);
+        }
+
+        Ok(Some(step))
+    }
+
+    /// Runs until the machine halts or hits a breakpoint. Running again
+    /// continues from there.
+    pub fn run(&mut self) -> eyre::Result<Stop> {
+        loop {
+            let instruction_pointer = self.machine.instruction_pointer;
+            let breakpoint = Breakpoint::InstructionPointer(instruction_pointer);
+            let resuming = self.stopped_at.take() == Some(instruction_pointer);
+            if !resuming && self.breakpoints.contains(&breakpoint) {
+                self.stopped_at = Some(instruction_pointer);
+                return Ok(Stop::Breakpoint(breakpoint));
+            }
+
+            if let Some(max_steps) = self.max_steps {
+                if self.steps >= max_steps && !self.is_halted() {
+                    let error = MachineError::TooManySteps {
+                        instruction_pointer,
+                        steps: max_steps,
+                    };
+
+                    return Err(error).wrap_err_with(|| {
+                        format!("In this program:\n{}", asm::disassemble(self.program))
+                    });
+                }
+            }
+
+            let Some(step) = self.step()? else {
+                return Ok(Stop::Halted);
+            };
+
+            let breakpoint = Breakpoint::Outputs(self.output.len());
+            if step.output.is_some() && self.breakpoints.contains(&breakpoint) {
+                return Ok(Stop::Breakpoint(breakpoint));
+            }
+        }
+    }
+}
+
+#[test]
+fn breakpoints_and_limits() {
+    let program = [0, 1, 5, 4, 3, 0].map(|value| U3::try_from(value).unwrap());
+    let machine = Machine {
+        reg_a: 729,
+        ..Default::default()
+    };
+
+    let mut debugger = Debugger::new(machine, &program)
+        .with_breakpoint(Breakpoint::InstructionPointer(4))
+        .with_breakpoint(Breakpoint::Outputs(3))
+        .recording();
+
+    assert_eq!(debugger.run().unwrap(), Stop::Breakpoint(Breakpoint::InstructionPointer(4)));
+    assert_eq!(debugger.output.len(), 1);
+    assert_eq!(debugger.trace.len(), 2);
+
+    let step = debugger.trace[1];
+    assert_eq!(
+        (step.instruction, step.combo),
+        (Instruction::Output, Some(364))
+    );
+    assert_eq!(step.output, Some(U3::try_from(4).unwrap()));
+    assert_eq!(
+        step.to_string(),
+        "  2: out 4 (combo 364) | A=364 B=0 C=0 -> A=364 B=0 C=0 | out 4"
+    );
+
+    assert_eq!(
+        debugger.run().unwrap(),
+        Stop::Breakpoint(Breakpoint::InstructionPointer(4))
+    );
+    assert_eq!(
+        debugger.run().unwrap(),
+        Stop::Breakpoint(Breakpoint::Outputs(3))
+    );
+    assert_eq!(
+        debugger
+            .output
+            .iter()
+            .map(|value| value.value())
+            .collect::<Vec<_>>(),
+        [4, 6, 3]
+    );
+
+    // A breakpoint where it starts stops it before the first step, but not
+    // again when resuming from there.
+    let mut debugger =
+        Debugger::new(machine, &program).with_breakpoint(Breakpoint::InstructionPointer(0));
+    assert_eq!(
+        debugger.run().unwrap(),
+        Stop::Breakpoint(Breakpoint::InstructionPointer(0))
+    );
+    assert_eq!(debugger.machine.instruction_pointer, 0);
+    assert_eq!(
+        debugger.run().unwrap(),
+        Stop::Breakpoint(Breakpoint::InstructionPointer(0))
+    );
+    assert_eq!(debugger.machine.instruction_pointer, 0);
+    assert_eq!(debugger.output.len(), 1);
+
+    // Jumping back to the start with A never reaching 0.
+    let program = [3, 0].map(|value| U3::try_from(value).unwrap());
+    let machine = Machine {
+        reg_a: 1,
+        ..Default::default()
+    };
+
+    let error = Debugger::new(machine, &program)
+        .with_max_steps(100)
+        .run()
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<MachineError>(),
+        Some(&MachineError::TooManySteps {
+            instruction_pointer: 0,
+            steps: 100
+        })
+    );
+    assert!(error.to_string().contains("jnz L0"), "{error}");
+
+    assert_eq!(
+        "ip 4".parse::<Breakpoint>().unwrap(),
+        Breakpoint::InstructionPointer(4)
+    );
+    assert_eq!(
+        " out 3 ".parse::<Breakpoint>().unwrap(),
+        Breakpoint::Outputs(3)
+    );
+    assert!("halt 3".parse::<Breakpoint>().is_err());
+}
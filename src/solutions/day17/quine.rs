@@ -0,0 +1,134 @@
+//! Finds the value of register A that makes a program output itself.
+//!
+//! This only works for programs shaped like the inputs: a loop that outputs a
+//! value, shifts A by 3 bits with `adv 3` and jumps back to the start with
+//! `jnz 0` until A is 0. B and C are computed from A in every iteration, so
+//! each output only depends on A at the start of its iteration, which is
+//! `A >> 3k` for the `k`th output. That means A can be built 3 bits at a time,
+//! from the last output backwards.
+
+use color_eyre::eyre;
+
+use super::{compiled::Compiled, Instruction, Machine, Operand, Register, U3};
+
+/// Checks that `program` is a loop the solver understands.
+pub fn check_shape(program: &[U3]) -> eyre::Result<()> {
+    use Instruction as I;
+
+    let instructions = super::instructions(program)?;
+
+    let count = |instruction: Instruction| {
+        instructions
+            .iter()
+            .filter(|(other, _)| *other == instruction)
+            .count()
+    };
+
+    eyre::ensure!(
+        count(I::JumpNotZero) == 1 && instructions.last() == Some(&(I::JumpNotZero, U3::ZERO)),
+        "The program should end with its only jump, `jnz 0`"
+    );
+    eyre::ensure!(
+        count(I::DivisionA) == 1 && instructions.contains(&(I::DivisionA, U3::new(3).unwrap())),
+        "The program should shift A with a single `adv 3`"
+    );
+    eyre::ensure!(count(I::Output) == 1, "The program should output once per loop");
+
+    let mut written = Vec::new();
+    for &(instruction, operand) in &instructions {
+        let mut reads = Vec::new();
+        if instruction.operand() == Operand::Combo {
+            reads.extend(Register::from_combo(operand));
+        }
+
+        let writes = match instruction {
+            I::BitwiseBXorOp => {
+                reads.push(Register::B);
+                Some(Register::B)
+            }
+            I::BitwiseBXorC => {
+                reads.extend([Register::B, Register::C]);
+                Some(Register::B)
+            }
+            I::Modulo8 | I::DivisionB => Some(Register::B),
+            I::DivisionC => Some(Register::C),
+            I::DivisionA | I::JumpNotZero | I::Output => None,
+        };
+
+        if let Some(register) = reads
+            .into_iter()
+            .find(|&register| register != Register::A && !written.contains(&register))
+        {
+            eyre::bail!(
+                "The program should set {register:?} from A before reading it in each loop"
+            );
+        }
+
+        written.extend(writes);
+    }
+
+    Ok(())
+}
+
+/// The smallest value of register A for which `program` outputs itself.
+pub fn smallest(machine: Machine, program: &[U3]) -> eyre::Result<u64> {
+    check_shape(program)?;
+    let compiled = Compiled::new(program);
+
+    // The values of A at the start of the current iteration that output the
+    // rest of the program.
+    let mut candidates = vec![0];
+    for &expected in program.iter().rev() {
+        // If A was 0 the previous iteration wouldn't have jumped back.
+        let next = candidates
+            .iter()
+            .flat_map(|candidate| (0..8).map(move |bits| candidate << 3 | bits))
+            .filter(|&reg_a| reg_a != 0)
+            .collect::<Vec<_>>();
+
+        let mut outputs = compiled.run_many(machine, next, 1);
+        outputs.retain(|(_, output)| {
+            output
+                .as_ref()
+                .is_ok_and(|output| output[..] == [expected.value()])
+        });
+        candidates = outputs.into_iter().map(|(reg_a, _)| reg_a).collect();
+    }
+
+    // The shape makes sure these halt, since A gets shorter every iteration.
+    candidates.sort_unstable();
+    compiled
+        .find_first(machine, candidates, program)
+        .ok_or_else(|| eyre::eyre!("No value of register A makes the program output itself"))
+}
+
+#[test]
+fn shapes() {
+    let program = |values: &[u8]| {
+        values
+            .iter()
+            .map(|&value| U3::try_from(value).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert!(check_shape(&program(&[0, 3, 5, 4, 3, 0])).is_ok());
+    assert!(check_shape(&program(&[2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0])).is_ok());
+
+    let error = check_shape(&program(&[0, 1, 5, 4, 3, 0])).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The program should shift A with a single `adv 3`"
+    );
+
+    let error = check_shape(&program(&[0, 3, 5, 4, 3, 2])).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The program should end with its only jump, `jnz 0`"
+    );
+
+    let error = check_shape(&program(&[1, 1, 0, 3, 5, 5, 3, 0])).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The program should set B from A before reading it in each loop"
+    );
+}
@@ -0,0 +1,340 @@
+//! Runs a program on expressions instead of numbers, to get what each output
+//! is as a formula of A.
+//!
+//! The program has to be a loop without any jumps except for the `jnz 0` at the
+//! end, like the inputs. A single iteration is lifted in terms of the registers
+//! at its start, which can then be unrolled for as many iterations as needed.
+
+use std::fmt;
+
+use color_eyre::eyre;
+
+use super::{Instruction, Machine, Register, U3};
+
+/// An expression over the registers at the start of an iteration (or of the
+/// program, once unrolled).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Register(Register),
+    Const(u64),
+    Shr(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Mod8(Box<Expr>),
+}
+
+impl Expr {
+    pub fn shr(value: Expr, shift: Expr) -> Expr {
+        match (value, shift) {
+            (Expr::Const(value), Expr::Const(shift)) => {
+                let shift = u32::try_from(shift).unwrap_or(u32::MAX);
+                Expr::Const(value.checked_shr(shift).unwrap_or(0))
+            }
+            (value, Expr::Const(0)) => value,
+            (Expr::Shr(value, inner), Expr::Const(shift)) if matches!(*inner, Expr::Const(_)) => {
+                let Expr::Const(inner) = *inner else {
+                    unreachable!()
+                };
+                Expr::shr(*value, Expr::Const(inner.saturating_add(shift)))
+            }
+            (value, shift) => Expr::Shr(Box::new(value), Box::new(shift)),
+        }
+    }
+
+    pub fn xor(left: Expr, right: Expr) -> Expr {
+        fn terms(expr: Expr, out: &mut Vec<Expr>) {
+            match expr {
+                Expr::Xor(left, right) => {
+                    terms(*left, out);
+                    terms(*right, out);
+                }
+                expr => out.push(expr),
+            }
+        }
+
+        let mut all = Vec::new();
+        terms(left, &mut all);
+        terms(right, &mut all);
+
+        // Constants go together at the end, and anything that shows up twice
+        // cancels out.
+        let mut constant = 0;
+        let mut kept = Vec::<Expr>::new();
+        for term in all {
+            if let Expr::Const(value) = term {
+                constant ^= value;
+            } else if let Some(index) = kept.iter().position(|other| *other == term) {
+                kept.remove(index);
+            } else {
+                kept.push(term);
+            }
+        }
+
+        if constant != 0 || kept.is_empty() {
+            kept.push(Expr::Const(constant));
+        }
+
+        kept.into_iter()
+            .reduce(|left, right| Expr::Xor(Box::new(left), Box::new(right)))
+            .unwrap()
+    }
+
+    pub fn mod8(value: Expr) -> Expr {
+        match value {
+            Expr::Const(value) => Expr::Const(value % 8),
+            value @ Expr::Mod8(_) => value,
+            value => Expr::Mod8(Box::new(value)),
+        }
+    }
+
+    fn combo(operand: U3) -> eyre::Result<Expr> {
+        match Register::from_combo(operand) {
+            Some(register) => Ok(Expr::Register(register)),
+            None if operand.value() < 4 => Ok(Expr::Const(operand.value() as u64)),
+            None => eyre::bail!("Combo operand 7 is reserved"),
+        }
+    }
+
+    pub fn eval(&self, a: u64, b: u64, c: u64) -> u64 {
+        match self {
+            Expr::Register(Register::A) => a,
+            Expr::Register(Register::B) => b,
+            Expr::Register(Register::C) => c,
+            Expr::Const(value) => *value,
+            Expr::Shr(value, shift) => {
+                let shift = shift.eval(a, b, c);
+                value.eval(a, b, c).checked_shr(shift.try_into().unwrap_or(u32::MAX)).unwrap_or(0)
+            }
+            Expr::Xor(left, right) => left.eval(a, b, c) ^ right.eval(a, b, c),
+            Expr::Mod8(value) => value.eval(a, b, c) % 8,
+        }
+    }
+
+    pub fn mentions(&self, register: Register) -> bool {
+        match self {
+            Expr::Register(other) => *other == register,
+            Expr::Const(_) => false,
+            Expr::Shr(left, right) | Expr::Xor(left, right) => {
+                left.mentions(register) || right.mentions(register)
+            }
+            Expr::Mod8(value) => value.mentions(register),
+        }
+    }
+
+    /// Replaces the registers with other expressions.
+    pub fn substitute(&self, registers: &[Expr; 3]) -> Expr {
+        match self {
+            Expr::Register(register) => registers[*register as usize].clone(),
+            Expr::Const(value) => Expr::Const(*value),
+            Expr::Shr(value, shift) => {
+                Expr::shr(value.substitute(registers), shift.substitute(registers))
+            }
+            Expr::Xor(left, right) => {
+                Expr::xor(left.substitute(registers), right.substitute(registers))
+            }
+            Expr::Mod8(value) => Expr::mod8(value.substitute(registers)),
+        }
+    }
+
+    /// Shows the expression with `a` in place of register A.
+    pub fn display<'e>(&'e self, a: &'e str) -> impl fmt::Display + 'e {
+        Display { expr: self, a }
+    }
+}
+
+struct Display<'e> {
+    expr: &'e Expr,
+    a: &'e str,
+}
+
+impl Display<'_> {
+    fn child<'e>(&self, expr: &'e Expr, associative: bool) -> impl fmt::Display + 'e
+    where
+        Self: 'e,
+    {
+        let atom = matches!(
+            expr,
+            Expr::Register(Register::B | Register::C) | Expr::Const(_)
+        ) || (matches!(expr, Expr::Register(Register::A)) && !self.a.contains(' '))
+            || (associative && matches!(expr, Expr::Xor(..)));
+
+        let inner = Display { expr, a: self.a };
+        if atom {
+            format!("{inner}")
+        } else {
+            format!("({inner})")
+        }
+    }
+}
+
+impl fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.expr {
+            Expr::Register(Register::A) => write!(f, "{}", self.a),
+            Expr::Register(register) => write!(f, "{register:?}"),
+            Expr::Const(value) => write!(f, "{value}"),
+            Expr::Shr(value, shift) => write!(
+                f,
+                "{} >> {}",
+                self.child(value, false),
+                self.child(shift, false)
+            ),
+            Expr::Xor(left, right) => write!(
+                f,
+                "{} ^ {}",
+                self.child(left, true),
+                self.child(right, true)
+            ),
+            Expr::Mod8(value) => write!(f, "{} & 7", self.child(value, false)),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display("A"))
+    }
+}
+
+/// What a single iteration of the loop does, in terms of the registers at its
+/// start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Iteration {
+    pub outputs: Vec<Expr>,
+    /// The registers at the end, in order A, B and C.
+    pub registers: [Expr; 3],
+}
+
+/// Lifts the body of the loop.
+pub fn lift(program: &[U3]) -> eyre::Result<Iteration> {
+    use Instruction as I;
+
+    let instructions = super::instructions(program)?;
+
+    let mut registers = [Register::A, Register::B, Register::C].map(Expr::Register);
+    let mut outputs = Vec::new();
+
+    for (index, &(instruction, operand)) in instructions.iter().enumerate() {
+        let combo = || Expr::combo(operand).map(|combo| combo.substitute(&registers));
+        let [a, b, _] = registers.clone();
+
+        match instruction {
+            I::DivisionA => registers[0] = Expr::shr(a, combo()?),
+            I::DivisionB => registers[1] = Expr::shr(a, combo()?),
+            I::DivisionC => registers[2] = Expr::shr(a, combo()?),
+            I::BitwiseBXorOp => registers[1] = Expr::xor(b, Expr::Const(operand.value() as u64)),
+            I::BitwiseBXorC => registers[1] = Expr::xor(b, registers[2].clone()),
+            I::Modulo8 => registers[1] = Expr::mod8(combo()?),
+            I::Output => outputs.push(Expr::mod8(combo()?)),
+            I::JumpNotZero => eyre::ensure!(
+                index == instructions.len() - 1 && operand.value() == 0,
+                "Only loops with a single `jnz 0` at the end can be lifted"
+            ),
+        }
+    }
+
+    eyre::ensure!(
+        instructions.last().is_some_and(|&(instruction, _)| instruction == I::JumpNotZero),
+        "Only loops with a single `jnz 0` at the end can be lifted"
+    );
+
+    Ok(Iteration { outputs, registers })
+}
+
+impl Iteration {
+    /// The outputs of the first `iterations` iterations, in terms of the
+    /// registers at the start of the program. It assumes A doesn't get to 0
+    /// before then.
+    pub fn unroll(&self, iterations: usize) -> Vec<Expr> {
+        let mut registers = [Register::A, Register::B, Register::C].map(Expr::Register);
+        let mut outputs = Vec::new();
+
+        for _ in 0..iterations {
+            outputs.extend(
+                self.outputs
+                    .iter()
+                    .map(|output| output.substitute(&registers)),
+            );
+            registers = self
+                .registers
+                .clone()
+                .map(|register| register.substitute(&registers));
+        }
+
+        outputs
+    }
+
+    /// Whether the unrolled outputs, evaluated with the registers of `machine`,
+    /// are the `expected` outputs of running the program.
+    pub fn matches(&self, machine: &Machine, expected: &[U3]) -> bool {
+        let iterations = expected.len().div_ceil(self.outputs.len().max(1));
+        let outputs = self.unroll(iterations);
+
+        outputs.len() >= expected.len()
+            && outputs.iter().zip(expected).all(|(output, expected)| {
+                output.eval(machine.reg_a, machine.reg_b, machine.reg_c) == expected.value() as u64
+            })
+    }
+
+    /// The outputs as formulas of the `k`th iteration, if A is only shifted
+    /// by a constant and B and C don't carry over between iterations.
+    pub fn formulas(&self) -> Option<String> {
+        let Expr::Shr(value, shift) = &self.registers[0] else {
+            return None;
+        };
+        let (Expr::Register(Register::A), Expr::Const(shift)) = (&**value, &**shift) else {
+            return None;
+        };
+
+        if self
+            .outputs
+            .iter()
+            .any(|output| output.mentions(Register::B) || output.mentions(Register::C))
+        {
+            return None;
+        }
+
+        let a = format!("A >> {shift}k");
+        let formulas = self
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(index, output)| {
+                let name = if self.outputs.len() == 1 {
+                    "output_k".to_string()
+                } else {
+                    format!("output_k.{index}")
+                };
+                format!("{name} = {}", output.display(&a))
+            })
+            .collect::<Vec<_>>();
+
+        Some(formulas.join("\n"))
+    }
+}
+
+#[test]
+fn lifting() {
+    let program =
+        [2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0].map(|value| U3::try_from(value).unwrap());
+    let iteration = lift(&program).unwrap();
+
+    assert_eq!(iteration.registers[0].to_string(), "A >> 3");
+    assert_eq!(iteration.outputs[0].to_string(), "((A & 7) ^ (A >> ((A & 7) ^ 1)) ^ 5) & 7");
+    assert_eq!(
+        iteration.formulas().unwrap(),
+        "output_k = (((A >> 3k) & 7) ^ ((A >> 3k) >> (((A >> 3k) & 7) ^ 1)) ^ 5) & 7"
+    );
+
+    for reg_a in [1, 729, 2024, 117440, 202991746427434] {
+        let machine = Machine {
+            reg_a,
+            ..Default::default()
+        };
+        let mut running = machine;
+        let expected = running.run(&program).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert!(iteration.matches(&machine, &expected), "A = {reg_a}");
+    }
+
+    assert!(lift(&[3, 2, 5, 4].map(|value| U3::try_from(value).unwrap())).is_err());
+}
@@ -1,92 +1,177 @@
-use pathfinding::{
-    directed::{bfs::bfs, dijkstra::dijkstra},
-    matrix::directions::DIRECTIONS_4,
+use color_eyre::eyre::{self, OptionExt as _};
+use ndarray::Array2;
+use pathfinding::directed::bfs::bfs;
+use petgraph::unionfind::UnionFind;
+use winnow::{combinator::separated_pair, Parser as _};
+
+use crate::{
+    answer::Answer,
+    grid::{Grid, Position},
+    parse::{self, ParseError},
+    vec2::{Vec2, Vec2Ext as _},
 };
-use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
 
-pub type Vec2 = nalgebra::Vector2<i32>;
+/// A `size` grid with the index of the first obstacle that falls on each tile.
+fn fallen(size: Vec2, obstacles: &[Vec2]) -> eyre::Result<Grid<Option<usize>>> {
+    let (width, height) = size.to_position().ok_or_eyre("The grid can't have a negative size")?;
+    let mut grid = Grid::from_array(Array2::from_elem((height, width), None));
+
+    for (index, obstacle) in obstacles.iter().enumerate() {
+        let tile = obstacle
+            .to_position()
+            .and_then(|position| grid.get_mut(position));
+        let tile = tile.ok_or_else(|| {
+            eyre::eyre!(
+                "The obstacle at {},{} is outside of the grid",
+                obstacle.x,
+                obstacle.y
+            )
+        })?;
+        tile.get_or_insert(index);
+    }
+
+    Ok(grid)
+}
 
-fn solve(input: &str, take: usize, bounds: Vec2) -> usize {
-    let obstacles = parse(input.trim()).take(take).collect::<Vec<_>>();
-    let obstacles = &obstacles;
+fn solve(input: &str, take: usize, exit: Vec2) -> eyre::Result<usize> {
+    let mut obstacles = parse(input.trim())?;
+    obstacles.truncate(take);
+    let blocked = fallen(exit + Vec2::new(1, 1), &obstacles)?.map(Option::is_some);
 
-    let (_path, cost) = dijkstra(
+    let path = bfs(
         &Vec2::new(0, 0),
-        |position| {
-            DIRECTIONS_4
-                .into_iter()
-                .map(|(dx, dy)| *position + Vec2::new(dx as i32, dy as i32))
-                .filter(move |p| {
-                    p.partial_cmp(&bounds).is_some_and(|cmp| cmp.is_le())
-                        && p.partial_cmp(&Vec2::zeros()).is_some_and(|cmp| cmp.is_ge())
-                        && !obstacles.contains(&p)
-                })
-                .map(|p| (p, 1))
+        |&position| {
+            position
+                .neighbors4()
+                .filter(|&p| blocked.at(p) == Some(&false))
                 .collect::<Vec<_>>()
         },
-        |position| *position == bounds,
+        |&position| position == exit,
     )
-    .unwrap();
+    .ok_or_eyre("There's no path to the exit")?;
 
-    cost
+    Ok(path.len() - 1)
 }
 
 #[elvish::solution(day = 18)]
-fn part1(input: &str) -> usize {
-    solve(input, 1024, Vec2::new(70, 70))
+fn part1(input: &str) -> Answer<usize> {
+    solve(input, 1024, Vec2::new(70, 70)).into()
 }
 
 #[test]
 fn example_part1() {
-    let output = solve(EXAMPLE_PART1, 12, Vec2::new(6, 6));
+    let output = solve(EXAMPLE_PART1, 12, Vec2::new(6, 6)).unwrap();
     assert_eq!(output, 22);
 }
 
-fn solve2(input: &str, bounds: Vec2) -> String {
-    // let mut obstacles_iter = parse(input.trim());
-    // let mut obstacles = Vec::new();
-
-    let obstacles = parse(input.trim()).collect::<Vec<_>>();
-
-    (0..obstacles.len()).into_par_iter().find_map_first(|i| {
-        let obstacles_ref = &obstacles[0..i];
-
-        let result = bfs(
-            &Vec2::new(0, 0),
-            |position| {
-                DIRECTIONS_4
-                    .into_iter()
-                    .map(|(dx, dy)| *position + Vec2::new(dx as i32, dy as i32))
-                    .filter(move |p| {
-                        p.partial_cmp(&bounds).is_some_and(|cmp| cmp.is_le())
-                            && p.partial_cmp(&Vec2::zeros()).is_some_and(|cmp| cmp.is_ge())
-                            && !obstacles_ref.contains(&p)
-                    })
-                    .collect::<Vec<_>>()
-            },
-            |position| *position == bounds,
-        );
-
-        if result.is_none() {
-            let [x, y] = obstacles[i - 1].into();
-            Some(format!("{x},{y}"))
-        } else {
-            None
+/// The index of the first of `obstacles` that cuts `start` off from `goal`
+/// once they've fallen in order on a `size` grid, or `None` if they never do.
+///
+/// It starts with all of them in place and takes them away from the last one,
+/// joining each tile that frees up with its free neighbours. The first one
+/// whose removal connects `start` and `goal` is the one that blocked them.
+pub fn first_blocking(
+    size: Vec2,
+    start: Vec2,
+    goal: Vec2,
+    obstacles: &[Vec2],
+) -> eyre::Result<Option<usize>> {
+    let fallen = fallen(size, obstacles)?;
+    let mut blocked = fallen.map(Option::is_some);
+    let width = blocked.width();
+    let id = |(x, y): Position| y * width + x;
+
+    let [start, goal] = [start, goal].map(|position| {
+        position
+            .to_position()
+            .filter(|&position| blocked.contains(position))
+            .ok_or_eyre("The start and goal should be inside the grid")
+    });
+    let (start, goal) = (id(start?), id(goal?));
+
+    let mut components = UnionFind::new(blocked.width() * blocked.height());
+    let free = |blocked: &Grid<bool>, components: &mut UnionFind<usize>, position: Position| {
+        for neighbor in blocked.neighbors4(position) {
+            if blocked.get(neighbor) == Some(&false) {
+                components.union(id(position), id(neighbor));
+            }
+        }
+    };
+
+    for (position, _) in blocked.iter().filter(|(_, &blocked)| !blocked) {
+        free(&blocked, &mut components, position);
+    }
+
+    if components.equiv(start, goal) {
+        return Ok(None);
+    }
+
+    for (index, obstacle) in obstacles.iter().enumerate().rev() {
+        let position = obstacle.to_position().unwrap();
+
+        // Obstacles can fall on the same tile more than once, and it's only
+        // free before the first time.
+        if fallen.get(position) != Some(&Some(index)) {
+            continue;
+        }
+
+        *blocked.get_mut(position).unwrap() = false;
+        free(&blocked, &mut components, position);
+
+        if components.equiv(start, goal) {
+            return Ok(Some(index));
         }
-    }).unwrap()
+    }
+
+    eyre::bail!("The start and goal aren't connected even without any obstacles")
+}
+
+fn solve2(input: &str, exit: Vec2) -> eyre::Result<String> {
+    let obstacles = parse(input.trim())?;
+
+    let index = first_blocking(exit + Vec2::new(1, 1), Vec2::new(0, 0), exit, &obstacles)?
+        .ok_or_eyre("No obstacle blocks the exit")?;
+
+    let [x, y] = obstacles[index].into();
+    Ok(format!("{x},{y}"))
 }
 
 #[elvish::solution(day = 18)]
-fn part2(input: &str) -> String {
-    solve2(input, Vec2::new(70, 70))
+fn part2(input: &str) -> Answer<String> {
+    solve2(input, Vec2::new(70, 70)).into()
 }
 
 #[test]
 fn example_part2() {
-    let output = solve2(EXAMPLE_PART2, Vec2::new(6, 6));
+    let output = solve2(EXAMPLE_PART2, Vec2::new(6, 6)).unwrap();
     assert_eq!(output.as_str(), "6,1");
 }
 
+#[test]
+fn blocking() {
+    let obstacles = [(1, 0), (1, 1), (1, 1), (1, 2), (0, 0)].map(|(x, y)| Vec2::new(x, y));
+    let first = |obstacles: &[Vec2]| {
+        first_blocking(Vec2::new(3, 3), Vec2::new(0, 0), Vec2::new(2, 2), obstacles)
+    };
+
+    assert_eq!(first(&obstacles).unwrap(), Some(3));
+    assert_eq!(first(&obstacles[..3]).unwrap(), None);
+    assert_eq!(first(&[Vec2::new(0, 0)]).unwrap(), Some(0));
+    assert!(first(&[Vec2::new(3, 0)]).is_err());
+
+    // A wall across a big grid, with the gap closing last.
+    let size = 1000;
+    let mut wall = (1..size)
+        .map(|x| Vec2::new(x, size / 2))
+        .collect::<Vec<_>>();
+    wall.push(Vec2::new(0, size / 2));
+    let goal = Vec2::new(size - 1, size - 1);
+    assert_eq!(
+        first_blocking(Vec2::new(size, size), Vec2::zeros(), goal, &wall).unwrap(),
+        Some(wall.len() - 1)
+    );
+}
+
 elvish::example!(
     "
         5,4
@@ -117,12 +202,7 @@ elvish::example!(
     "
 );
 
-fn parse(input: &str) -> impl Iterator<Item = Vec2> + use<'_> {
-    input.lines().map(|line| {
-        let mut parts = line.split(',').map(|x| x.parse().unwrap());
-        let x = parts.next().unwrap();
-        let y = parts.next().unwrap();
-
-        Vec2::new(x, y)
-    })
+pub(super) fn parse(input: &str) -> Result<Vec<Vec2>, ParseError> {
+    let position = separated_pair(parse::int, ',', parse::int).map(|(x, y)| Vec2::new(x, y));
+    parse::all(input, parse::lines(position))
 }
@@ -1,15 +1,19 @@
-use std::collections::HashMap;
-
+use color_eyre::eyre;
 use winnow::{
-    ascii::{alpha1, multispace1},
-    combinator::separated,
-    seq, PResult, Parser,
+    combinator::{alt, repeat, separated_pair},
+    error::StrContext,
+    PResult, Parser,
+};
+
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError},
 };
 
-type Pattern = Vec<Color>;
+pub(super) type Pattern = Vec<Color>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Color {
+pub(super) enum Color {
     White,
     Blue,
     Black,
@@ -18,91 +22,191 @@ enum Color {
 }
 
 impl Color {
-    fn from_char(char: char) -> Self {
-        match char {
-            'w' => Self::White,
-            'u' => Self::Blue,
-            'b' => Self::Black,
-            'r' => Self::Red,
-            'g' => Self::Green,
-            _ => panic!("Invalid char {char}"),
-        }
+    fn parse(input: &mut &str) -> PResult<Self> {
+        alt((
+            'w'.value(Self::White),
+            'u'.value(Self::Blue),
+            'b'.value(Self::Black),
+            'r'.value(Self::Red),
+            'g'.value(Self::Green),
+        ))
+        .context(StrContext::Label("color"))
+        .parse_next(input)
     }
 }
 
-fn ways_to_make<'p>(
-    pattern: &'p [Color],
-    extensions: &[Pattern],
-    cache: &mut HashMap<&'p [Color], usize>,
-) -> usize {
-    if let Some(&output) = cache.get(pattern) {
-        return output;
+/// The available towels, in a trie so that all the ones that match at a
+/// position of a design are found in a single walk.
+pub(super) struct TowelSet {
+    towels: Vec<Pattern>,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: [Option<usize>; 5],
+    /// The towel that ends here, if any.
+    towel: Option<usize>,
+}
+
+impl TowelSet {
+    pub fn new(towels: Vec<Pattern>) -> Self {
+        let mut nodes = vec![Node::default()];
+
+        for (index, towel) in towels.iter().enumerate() {
+            let mut node = 0;
+            for &color in towel {
+                node = match nodes[node].children[color as usize] {
+                    Some(child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        nodes[node].children[color as usize] = Some(nodes.len() - 1);
+                        nodes.len() - 1
+                    }
+                };
+            }
+
+            nodes[node].towel.get_or_insert(index);
+        }
+
+        Self { towels, nodes }
     }
 
-    if pattern.is_empty() {
-        return 1;
+    /// The towels that match `design` starting at `start`, shortest first, with
+    /// where each of them ends.
+    fn matches<'s>(
+        &'s self,
+        design: &'s [Color],
+        start: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + 's {
+        design[start..]
+            .iter()
+            .scan(0, |node, &color| {
+                *node = self.nodes[*node].children[color as usize]?;
+                Some(*node)
+            })
+            .zip(start + 1..)
+            .filter_map(|(node, end)| Some((self.nodes[node].towel?, end)))
     }
 
-    let output = extensions
-        .into_iter()
-        .filter_map(|extension| {
-            if extension.len() > pattern.len() {
-                return None;
+    /// Whether the rest of the design can be made from each position.
+    fn possible(&self, design: &[Color]) -> Vec<bool> {
+        let mut possible = vec![false; design.len() + 1];
+        possible[design.len()] = true;
+
+        for start in (0..design.len()).rev() {
+            possible[start] = self.matches(design, start).any(|(_, end)| possible[end]);
+        }
+
+        possible
+    }
+
+    /// How many ways there are to arrange towels into `design`.
+    pub fn count(&self, design: &[Color]) -> eyre::Result<usize> {
+        // How many ways there are to make the design up to each position.
+        let mut ways = vec![0usize; design.len() + 1];
+        ways[0] = 1;
+
+        for start in 0..design.len() {
+            if ways[start] == 0 {
+                continue;
             }
-            let slice = &pattern[pattern.len() - extension.len()..pattern.len()];
-            if extension.as_slice() == slice {
-                Some(ways_to_make(
-                    &pattern[0..pattern.len() - extension.len()],
-                    extensions,
-                    cache,
-                ))
-            } else {
-                None
+
+            for (_, end) in self.matches(design, start) {
+                ways[end] = ways[end].checked_add(ways[start]).ok_or_else(|| {
+                    eyre::eyre!("There are too many ways to make a design to count")
+                })?;
             }
-        })
-        .sum();
+        }
+
+        Ok(ways[design.len()])
+    }
 
-    cache.insert(pattern, output);
-    output
+    /// The towels of the first arrangement that makes `design`, trying shorter
+    /// towels first.
+    pub fn first(&self, design: &[Color]) -> Option<Vec<&[Color]>> {
+        let possible = self.possible(design);
+        if !possible[0] {
+            return None;
+        }
+
+        let mut arrangement = Vec::new();
+        let mut start = 0;
+        while start < design.len() {
+            let (towel, end) = self.matches(design, start).find(|&(_, end)| possible[end]).unwrap();
+            arrangement.push(self.towels[towel].as_slice());
+            start = end;
+        }
+
+        Some(arrangement)
+    }
+
+    /// Every arrangement that makes `design`, in the same order as
+    /// [`TowelSet::first`]. There can be a lot of them, so they're made as
+    /// they're needed.
+    pub fn arrangements<'s>(
+        &'s self,
+        design: &'s [Color],
+    ) -> impl Iterator<Item = Vec<&'s [Color]>> + 's {
+        let possible = self.possible(design);
+
+        // Arrangements so far, with where they're up to.
+        let mut stack = if possible[0] { vec![(Vec::new(), 0)] } else { Vec::new() };
+
+        std::iter::from_fn(move || loop {
+            let (arrangement, start) = stack.pop()?;
+            if start == design.len() {
+                return Some(arrangement);
+            }
+
+            let next = self
+                .matches(design, start)
+                .filter(|&(_, end)| possible[end])
+                .collect::<Vec<_>>();
+            for (towel, end) in next.into_iter().rev() {
+                let mut arrangement = arrangement.clone();
+                arrangement.push(self.towels[towel].as_slice());
+                stack.push((arrangement, end));
+            }
+        })
+    }
 }
 
 #[elvish::solution(day = 19, example = 6)]
-fn part1(input: &str) -> usize {
-    let (available_extensions, target_patterns) = parse(input);
-
-    let mut cache = HashMap::new();
-    target_patterns
-        .iter()
-        .filter(|&pattern| ways_to_make(pattern, &available_extensions, &mut cache) > 0)
-        .count()
+fn part1(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let (towels, designs) = parse(input)?;
+        let towels = TowelSet::new(towels);
+
+        Ok(designs.iter().filter(|design| towels.first(design).is_some()).count())
+    })
 }
 
 #[elvish::solution(day = 19, example = 16)]
-fn part2(input: &str) -> usize {
-    let (available_extensions, target_patterns) = parse(input);
-
-    let mut cache = HashMap::new();
-    target_patterns
-        .iter()
-        .map(|pattern| ways_to_make(pattern, &available_extensions, &mut cache))
-        .sum()
-}
+fn part2(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let (towels, designs) = parse(input)?;
+        let towels = TowelSet::new(towels);
 
-fn parse(input: &str) -> (Vec<Pattern>, Vec<Pattern>) {
-    let pat = |input: &mut &str| -> PResult<Pattern> {
-        alpha1
-            .map(|pat: &str| pat.chars().map(Color::from_char).collect::<Vec<_>>())
-            .parse_next(input)
-    };
+        let mut total = 0usize;
+        for design in &designs {
+            total = total
+                .checked_add(towels.count(design)?)
+                .ok_or_else(|| eyre::eyre!("There are too many ways to make the designs to count"))?;
+        }
+
+        Ok(total)
+    })
+}
 
-    let mut parser = seq!((
-        separated(0.., pat, ", "),
-        _: multispace1,
-        separated(0.., pat, '\n'),
-        _: '\n'
-    ));
+pub(super) fn parse(input: &str) -> Result<(Vec<Pattern>, Vec<Pattern>), ParseError> {
+    let pat =
+        |input: &mut &str| -> PResult<Pattern> { repeat(1.., Color::parse).parse_next(input) };
 
-    parser.parse(input).unwrap()
+    parse::all(
+        input,
+        separated_pair(parse::comma_list(pat), parse::blank_line, parse::lines(pat)),
+    )
 }
 
 elvish::example!(
@@ -119,3 +223,44 @@ elvish::example!(
         bbrgwb
     "
 );
+
+#[test]
+fn arrangements() {
+    let (towels, designs) = parse(EXAMPLE_PART1).unwrap();
+    let towels = TowelSet::new(towels);
+
+    let counts = designs.iter().map(|design| towels.count(design).unwrap()).collect::<Vec<_>>();
+    assert_eq!(counts, [2, 1, 4, 6, 0, 1, 2, 0]);
+
+    for (design, count) in designs.iter().zip(counts) {
+        assert_eq!(towels.arrangements(design).count(), count);
+        assert_eq!(towels.first(design), towels.arrangements(design).next());
+
+        for arrangement in towels.arrangements(design) {
+            assert_eq!(arrangement.concat(), *design);
+        }
+    }
+
+    let show = |arrangement: Vec<&[Color]>| {
+        arrangement
+            .iter()
+            .map(|towel| {
+                towel
+                    .iter()
+                    .map(|color| format!("{color:?}"))
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        show(towels.first(&designs[0]).unwrap()),
+        ["Black", "Red", "White-Red", "Red"]
+    );
+
+    // Every way of splitting a line of whites into ones and twos.
+    let towels = TowelSet::new(vec![vec![Color::White], vec![Color::White; 2]]);
+    assert_eq!(towels.count(&[Color::White; 80]).unwrap(), 37889062373143906);
+    assert!(towels.count(&[Color::White; 500]).is_err());
+    assert_eq!(towels.first(&[Color::White; 500]).unwrap().len(), 500);
+}
@@ -1,7 +1,14 @@
+use color_eyre::eyre;
 use core::fmt;
 use std::{collections::HashMap, fmt::Write, iter::repeat};
 
-type Vec2 = nalgebra::Vector2<i32>;
+use winnow::{combinator::terminated, error::StrContext, Parser as _};
+
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError},
+    vec2::Vec2,
+};
 
 #[derive(Debug, Clone, Copy)]
 enum Key {
@@ -14,15 +21,15 @@ impl Key {
         match *self {
             Self::A => Vec2::new(1, -2),
             Self::Digit(0) => Vec2::new(0, -2),
-            Self::Digit(num) => Vec2::new((num as i32 - 1) % 3 - 1, (num as i32 - 1) / 3 - 1),
+            Self::Digit(num) => Vec2::new((num as i64 - 1) % 3 - 1, (num as i64 - 1) / 3 - 1),
         }
     }
 
-    fn from_char(char: u8) -> Self {
+    fn from_char(char: u8) -> Option<Self> {
         match char {
-            b'A' => Self::A,
-            b'0'..=b'9' => Self::Digit(char - b'0'),
-            _ => panic!("Invalid char5 {}", char::from(char)),
+            b'A' => Some(Self::A),
+            b'0'..=b'9' => Some(Self::Digit(char - b'0')),
+            _ => None,
         }
     }
 }
@@ -145,36 +152,38 @@ impl DirKey {
         }
     }
 
-    fn map_x(delta: i32) -> Vec<Self> {
+    fn map_x(delta: i64) -> Vec<Self> {
         repeat(if delta > 0 { Self::Right } else { Self::Left })
             .take(delta.abs() as usize)
             .collect::<Vec<_>>()
     }
 
-    fn map_y(delta: i32) -> Vec<Self> {
+    fn map_y(delta: i64) -> Vec<Self> {
         repeat(if delta > 0 { Self::Up } else { Self::Down })
             .take(delta.abs() as usize)
             .collect::<Vec<_>>()
     }
 }
 
-fn solve(input: &str, robots_in_between: u32) -> u64 {
-    let codes = input
-        .lines()
-        .map(|line| line.bytes().map(Key::from_char).collect::<Vec<_>>());
+/// Each code is some digits followed by `A`, of which we also return the
+/// numeric part.
+fn parse(input: &str) -> Result<Vec<(Vec<Key>, u32)>, ParseError> {
+    let code = terminated(parse::uint::<u32>, 'A')
+        .with_taken()
+        .map(|(numeric, code)| {
+            let keys = code.bytes().map(|char| Key::from_char(char).expect("Only digits and `A`"));
+            (keys.collect(), numeric)
+        })
+        .context(StrContext::Label("code"));
 
-    let mut complexities = 0;
-    for code in codes {
-        let numeric: u32 = code[0..code.len() - 1]
-            .into_iter()
-            .rev()
-            .enumerate()
-            .map(|(i, digit)| {
-                let Key::Digit(digit) = digit else { panic!() };
-                *digit as u32 * 10u32.pow(i as u32)
-            })
-            .sum();
+    parse::all(input, parse::lines(code))
+}
+
+fn solve(input: &str, robots_in_between: u32) -> eyre::Result<u64> {
+    let codes = parse(input)?;
 
+    let mut complexities = 0;
+    for (code, numeric) in codes {
         let mut result = 0;
         let mut position = Key::A.position();
         let mut cache = HashMap::new();
@@ -189,17 +198,17 @@ fn solve(input: &str, robots_in_between: u32) -> u64 {
         complexities += complexity;
     }
 
-    complexities
+    Ok(complexities)
 }
 
 #[elvish::solution(day = 21, example = 126384)]
-fn part1(input: &str) -> u64 {
-    solve(input, 2)
+fn part1(input: &str) -> Answer<u64> {
+    solve(input, 2).into()
 }
 
 #[elvish::solution(day = 21)]
-fn part2(input: &str) -> u64 {
-    solve(input, 25)
+fn part2(input: &str) -> Answer<u64> {
+    solve(input, 25).into()
 }
 
 // Debugging, looks cool so I'm keeping it in.
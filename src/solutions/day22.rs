@@ -5,6 +5,11 @@ use std::{
 
 use itertools::Itertools as _;
 
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError},
+};
+
 fn mix(secret: &mut i64, salt: i64) {
     *secret = *secret ^ salt;
 }
@@ -59,53 +64,59 @@ fn secret_123() {
     }
 }
 
+fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    parse::all(input, parse::lines(parse::uint))
+}
+
 #[elvish::solution(day = 22, example = 37327623)]
-fn part1(input: &str) -> i64 {
-    input
-        .lines()
-        .map(|line| line.parse().unwrap())
-        .map(|num| secret_numbers(num).take(2000).last().unwrap())
-        .sum()
+fn part1(input: &str) -> Answer<i64> {
+    Answer::new(|| {
+        Ok(parse(input)?
+            .into_iter()
+            .map(|num| secret_numbers(num).take(2000).last().unwrap())
+            .sum())
+    })
 }
 
 #[elvish::solution(day = 22, example = 23)]
-fn part2(input: &str) -> i64 {
-    let nums = input
-        .lines()
-        .map(|line| line.parse().unwrap())
-        .map(|initial| {
-            let nums = secret_numbers(initial)
-                .map(|num| num % 10)
-                .take(2000)
-                .collect::<Vec<_>>();
-
-            let diffs = once(&(initial % 10))
-                .chain(&nums)
-                .tuple_windows()
-                .map(|(a, b)| b - a)
-                .collect::<Vec<_>>();
-
-            (nums, diffs)
-        })
-        .collect::<Vec<_>>();
-
-    let diff_map = {
-        let mut diff_map = HashMap::new();
-        for (nums, diffs) in &nums {
-            let mut visited = HashSet::new();
-            for (i, window) in diffs.windows(4).enumerate() {
-                if !visited.insert(window) {
-                    continue;
+fn part2(input: &str) -> Answer<i64> {
+    Answer::new(|| {
+        let nums = parse(input)?
+            .into_iter()
+            .map(|initial| {
+                let nums = secret_numbers(initial)
+                    .map(|num| num % 10)
+                    .take(2000)
+                    .collect::<Vec<_>>();
+
+                let diffs = once(&(initial % 10))
+                    .chain(&nums)
+                    .tuple_windows()
+                    .map(|(a, b)| b - a)
+                    .collect::<Vec<_>>();
+
+                (nums, diffs)
+            })
+            .collect::<Vec<_>>();
+
+        let diff_map = {
+            let mut diff_map = HashMap::new();
+            for (nums, diffs) in &nums {
+                let mut visited = HashSet::new();
+                for (i, window) in diffs.windows(4).enumerate() {
+                    if !visited.insert(window) {
+                        continue;
+                    }
+                    let entry = diff_map.entry(window).or_insert(0);
+                    *entry += nums[i + 3];
                 }
-                let entry = diff_map.entry(window).or_insert(0);
-                *entry += nums[i + 3];
             }
-        }
 
-        diff_map
-    };
+            diff_map
+        };
 
-    *diff_map.values().max().unwrap()
+        Ok(*diff_map.values().max().unwrap_or(&0))
+    })
 }
 
 elvish::example!(
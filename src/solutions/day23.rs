@@ -4,17 +4,24 @@ use std::{
 };
 
 use itertools::Itertools as _;
+use winnow::{ascii::alpha1, combinator::separated_pair, error::StrContext, Parser as _};
+
+use crate::{
+    answer::Answer,
+    parse::{self, ParseError},
+};
 
 type Node<'a> = &'a [u8];
 
-fn parse(input: &str) -> (HashSet<Node>, HashMap<Node, HashSet<Node>>) {
+type Graph<'a> = (HashSet<Node<'a>>, HashMap<Node<'a>, HashSet<Node<'a>>>);
+
+pub(super) fn parse(input: &str) -> Result<Graph<'_>, ParseError> {
     let mut connections = HashMap::new();
     let mut nodes = HashSet::new();
 
-    for line in input.lines() {
-        let mut cities = line.split('-');
-        let a = cities.next().unwrap().as_bytes();
-        let b = cities.next().unwrap().as_bytes();
+    let connection = separated_pair(alpha1, '-', alpha1).context(StrContext::Label("connection"));
+    for (a, b) in parse::all(input, parse::lines(connection))? {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
 
         connections.entry(a).or_insert(HashSet::new()).insert(b);
         connections.entry(b).or_insert(HashSet::new()).insert(a);
@@ -22,82 +29,89 @@ fn parse(input: &str) -> (HashSet<Node>, HashMap<Node, HashSet<Node>>) {
         nodes.insert(b);
     }
 
-    (nodes, connections)
+    Ok((nodes, connections))
 }
 
 #[elvish::solution(day = 23, example = 7)]
-fn part1(input: &str) -> usize {
-    let (nodes, connections) = parse(input);
+fn part1(input: &str) -> Answer<usize> {
+    Answer::new(|| {
+        let (nodes, connections) = parse(input)?;
 
-    let mut trios = HashSet::new();
-    for node in &nodes {
-        if !node.starts_with(&[b't']) {
-            continue;
-        }
+        let mut trios = HashSet::new();
+        for node in &nodes {
+            if !node.starts_with(&[b't']) {
+                continue;
+            }
 
-        let neighbors = &connections[node];
-        for a in neighbors {
-            for b in neighbors {
-                if connections[a].contains(b) {
-                    let mut trio = [node, a, b];
-                    trio.sort();
-                    trios.insert(trio);
+            let neighbors = &connections[node];
+            for a in neighbors {
+                for b in neighbors {
+                    if connections[a].contains(b) {
+                        let mut trio = [node, a, b];
+                        trio.sort();
+                        trios.insert(trio);
+                    }
                 }
             }
         }
-    }
 
-    trios.len()
+        Ok(trios.len())
+    })
 }
 
 #[elvish::solution(day = 23)]
-fn part2(input: &str) -> String {
-    let (nodes, connections) = parse(input);
+fn part2(input: &str) -> Answer<String> {
+    Answer::new(|| {
+        let (nodes, connections) = parse(input)?;
 
-    let mut max_subset = vec![];
-    for node in nodes.iter() {
-        let mut queue = connections[node]
-            .iter()
-            .map(|&node| vec![node])
-            .collect::<Vec<_>>();
+        let mut max_subset = vec![];
+        for node in nodes.iter() {
+            let mut queue = connections[node]
+                .iter()
+                .map(|&node| vec![node])
+                .collect::<Vec<_>>();
 
-        let mut visited = HashSet::new();
+            let mut visited = HashSet::new();
 
-        while let Some(mut connected_nodes) = queue.pop() {
-            connected_nodes.sort();
-            if !visited.insert(connected_nodes.clone()) {
-                continue;
-            }
+            while let Some(mut connected_nodes) = queue.pop() {
+                connected_nodes.sort();
+                if !visited.insert(connected_nodes.clone()) {
+                    continue;
+                }
 
-            if connected_nodes.len() > max_subset.len() {
-                max_subset = connected_nodes.clone();
-            }
+                if connected_nodes.len() > max_subset.len() {
+                    max_subset = connected_nodes.clone();
+                }
 
-            let intersection = connected_nodes[1..]
-                .iter()
-                .map(|&node| connections[node].clone())
-                .fold(connections[connected_nodes[0]].clone(), |a, b| {
-                    a.intersection(&b).map(|&v| v).collect()
-                });
-
-            for next_node in intersection {
-                let mut next_nodes = connected_nodes.clone();
-                next_nodes.push(next_node);
-                queue.push(next_nodes);
+                let intersection = connected_nodes[1..]
+                    .iter()
+                    .map(|&node| connections[node].clone())
+                    .fold(connections[connected_nodes[0]].clone(), |a, b| {
+                        a.intersection(&b).map(|&v| v).collect()
+                    });
+
+                for next_node in intersection {
+                    let mut next_nodes = connected_nodes.clone();
+                    next_nodes.push(next_node);
+                    queue.push(next_nodes);
+                }
             }
         }
-    }
 
-    max_subset.sort();
-    max_subset
-        .into_iter()
-        .map(|node| from_utf8(node).unwrap())
-        .join(",")
+        max_subset.sort();
+        Ok(max_subset
+            .into_iter()
+            .map(|node| from_utf8(node).unwrap())
+            .join(","))
+    })
 }
 
 #[test]
 fn example_part2() {
-    assert_eq!(part2(EXAMPLE_PART2).as_str(), "co,de,ka,ta");
+    assert_eq!(
+        part2(EXAMPLE_PART2).into_result().unwrap().as_str(),
+        "co,de,ka,ta"
+    );
 }
 
 elvish::example!(
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::{self, WrapErr as _};
+
+use super::types::Equation;
+
+/// The wires computed by `equations`, each after the wires it depends on.
+/// Swapping outputs can create loops, which are an error.
+pub fn topological_order<'i>(
+    equations: &HashMap<&'i str, Equation<'i>>,
+) -> eyre::Result<Vec<&'i str>> {
+    #[derive(PartialEq, Eq)]
+    enum State {
+        Visiting,
+        Done,
+    }
+
+    fn visit<'i>(
+        wire: &'i str,
+        equations: &HashMap<&'i str, Equation<'i>>,
+        states: &mut HashMap<&'i str, State>,
+        order: &mut Vec<&'i str>,
+    ) -> eyre::Result<()> {
+        match states.get(wire) {
+            Some(State::Done) => return Ok(()),
+            Some(State::Visiting) => eyre::bail!("The circuit has a loop through `{wire}`"),
+            None => (),
+        }
+
+        // Otherwise it's an input.
+        let Some(equation) = equations.get(wire) else {
+            return Ok(());
+        };
+
+        states.insert(wire, State::Visiting);
+        for operand in equation.operands() {
+            visit(operand, equations, states, order)?;
+        }
+
+        states.insert(wire, State::Done);
+        order.push(wire);
+
+        Ok(())
+    }
+
+    let mut wires = equations.keys().copied().collect::<Vec<_>>();
+    wires.sort_unstable();
+
+    let mut states = HashMap::new();
+    let mut order = Vec::with_capacity(wires.len());
+    for wire in wires {
+        visit(wire, equations, &mut states, &mut order)?;
+    }
+
+    Ok(order)
+}
+
+/// The value of every wire, given the values of the inputs.
+pub fn evaluate<'i>(
+    inputs: &HashMap<&'i str, bool>,
+    equations: &HashMap<&'i str, Equation<'i>>,
+) -> eyre::Result<HashMap<&'i str, bool>> {
+    let mut values = inputs.clone();
+
+    for wire in topological_order(equations)? {
+        let equation = equations[wire];
+        let [a, b] = [equation.a, equation.b].map(|operand| {
+            values
+                .get(operand)
+                .copied()
+                .ok_or_else(|| eyre::eyre!("Wire `{operand}` has no value"))
+        });
+
+        values.insert(wire, equation.gate.compute(a?, b?));
+    }
+
+    Ok(values)
+}
+
+/// The number on the wires starting with `prefix`, where `z00` is the lowest
+/// bit of `z`, `z01` the next one and so on.
+pub fn read_number(values: &HashMap<&str, bool>, prefix: char) -> eyre::Result<u64> {
+    let mut number = 0;
+    for (wire, &value) in values {
+        let Some(bit) = wire.strip_prefix(prefix) else {
+            continue;
+        };
+
+        let bit = bit
+            .parse::<u32>()
+            .wrap_err_with(|| format!("Wire `{wire}` doesn't have a bit number"))?;
+
+        eyre::ensure!(bit < u64::BITS, "Wire `{wire}` doesn't fit in a `u64`");
+        number |= u64::from(value) << bit;
+    }
+
+    Ok(number)
+}
+
+#[test]
+fn loops_are_errors() {
+    use super::parse::parse;
+
+    let (inputs, equations) =
+        parse("x00: 1\n\nx00 AND bbb -> aaa\nx00 OR aaa -> bbb\nx00 XOR x00 -> z00").unwrap();
+
+    let error = evaluate(&inputs, &equations).unwrap_err();
+    assert!(
+        error.to_string().starts_with("The circuit has a loop"),
+        "{error}"
+    );
+
+    let mut equations = equations;
+    equations.remove("bbb");
+    let error = evaluate(&inputs, &equations).unwrap_err();
+    assert_eq!(error.to_string(), "Wire `bbb` has no value");
+
+    equations.remove("aaa");
+    let values = evaluate(&inputs, &equations).unwrap();
+    assert_eq!(read_number(&values, 'z').unwrap(), 0);
+    assert_eq!(read_number(&values, 'x').unwrap(), 1);
+}
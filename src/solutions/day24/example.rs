@@ -15,7 +15,7 @@ elvish::example!(
         y02 OR x01 -> tnw
         kwq OR kpj -> z05
         x00 OR x03 -> fst
-        tgd XOR rvg -> z0)
+        tgd XOR rvg -> z01
         vdt OR tnw -> bfw
         bfw AND frj -> z10
         ffh OR nrd -> bqk
@@ -49,3 +49,11 @@ elvish::example!(
         tnw OR pbm -> gnj
     ",
 );
+
+#[test]
+fn example_part1() {
+    assert_eq!(
+        super::part1::part1(EXAMPLE_PART1).into_result().unwrap(),
+        2024
+    );
+}
@@ -0,0 +1,220 @@
+//! Draws the gate network as a Graphviz (DOT) or Mermaid graph, to look at
+//! where the circuit stops being an adder.
+//!
+//! Every wire is a node, coloured by the gate that computes it. The inputs and
+//! outputs are grouped by bit, and the suspect wires are drawn in red.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    fs,
+    path::Path,
+};
+
+use color_eyre::eyre;
+use itertools::Itertools as _;
+
+use super::{
+    part2::adder::Suspect,
+    types::{Equation, Gate},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dot,
+    Mermaid,
+}
+
+impl Format {
+    /// Mermaid for `.mmd` and `.mermaid` files, DOT otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("mmd" | "mermaid") => Self::Mermaid,
+            _ => Self::Dot,
+        }
+    }
+}
+
+fn name(gate: Gate) -> &'static str {
+    match gate {
+        Gate::And => "AND",
+        Gate::Or => "OR",
+        Gate::Xor => "XOR",
+    }
+}
+
+fn colour(gate: Gate) -> &'static str {
+    match gate {
+        Gate::And => "#a6cee3",
+        Gate::Or => "#fdbf6f",
+        Gate::Xor => "#b2df8a",
+    }
+}
+
+/// The `x`, `y` and `z` wires of each bit. The top `z` bit is the carry, so it
+/// gets a group of its own.
+fn wires_by_bit<'i>(equations: &HashMap<&'i str, Equation<'i>>) -> BTreeMap<&'i str, Vec<&'i str>> {
+    let operands = equations.values().flat_map(|equation| equation.operands());
+    let outputs = equations.keys().copied().filter(|wire| wire.starts_with('z'));
+
+    let mut bits = BTreeMap::<_, Vec<_>>::new();
+    for wire in operands.chain(outputs) {
+        let bit = wire
+            .strip_prefix('x')
+            .or_else(|| wire.strip_prefix('y'))
+            .or_else(|| wire.strip_prefix('z'));
+        if let Some(bit) = bit {
+            bits.entry(bit).or_default().push(wire);
+        }
+    }
+
+    for wires in bits.values_mut() {
+        wires.sort_unstable();
+        wires.dedup();
+    }
+
+    bits
+}
+
+pub fn dot(equations: &HashMap<&str, Equation<'_>>, suspects: &[Suspect<'_>]) -> String {
+    let mut out = String::new();
+    writeln!(out, "digraph circuit {{").unwrap();
+    writeln!(out, "    rankdir=TB;").unwrap();
+    writeln!(out, "    node [shape=box, style=filled, fillcolor=white];").unwrap();
+
+    for (bit, wires) in wires_by_bit(equations) {
+        writeln!(
+            out,
+            "    subgraph cluster_{bit} {{ label=\"bit {bit}\"; {} }}",
+            wires.join("; ")
+        )
+        .unwrap();
+    }
+
+    for (wire, equation) in equations.iter().sorted_unstable_by_key(|(wire, _)| **wire) {
+        let gate = equation.gate;
+        write!(
+            out,
+            "    {wire} [label=\"{wire}\\n{}\", fillcolor=\"{}\"",
+            name(gate),
+            colour(gate)
+        )
+        .unwrap();
+        if let Some(suspect) = suspects.iter().find(|suspect| suspect.wire == *wire) {
+            write!(
+                out,
+                ", color=red, penwidth=3, tooltip=\"{}\"",
+                suspect.reason
+            )
+            .unwrap();
+        }
+        writeln!(out, "];").unwrap();
+
+        for operand in equation.operands() {
+            writeln!(out, "    {operand} -> {wire};").unwrap();
+        }
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+pub fn mermaid(equations: &HashMap<&str, Equation<'_>>, suspects: &[Suspect<'_>]) -> String {
+    // Wires are prefixed, since some (like `end`) are keywords in Mermaid.
+    let mut out = String::new();
+    writeln!(out, "flowchart TB").unwrap();
+
+    for gate in [Gate::And, Gate::Or, Gate::Xor] {
+        writeln!(out, "    classDef {} fill:{}", name(gate), colour(gate)).unwrap();
+    }
+    writeln!(out, "    classDef suspect stroke:#e31a1c,stroke-width:4px").unwrap();
+
+    for (bit, wires) in wires_by_bit(equations) {
+        writeln!(out, "    subgraph bit{bit} [bit {bit}]").unwrap();
+        for wire in wires {
+            writeln!(out, "        w_{wire}[{wire}]").unwrap();
+        }
+        writeln!(out, "    end").unwrap();
+    }
+
+    for (wire, equation) in equations.iter().sorted_unstable_by_key(|(wire, _)| **wire) {
+        let gate = name(equation.gate);
+        writeln!(out, "    w_{wire}[\"{wire} {gate}\"]:::{gate}").unwrap();
+        for operand in equation.operands() {
+            writeln!(out, "    w_{operand} --> w_{wire}").unwrap();
+        }
+    }
+
+    for suspect in suspects {
+        writeln!(out, "    class w_{} suspect", suspect.wire).unwrap();
+    }
+
+    out
+}
+
+/// Writes the graph to `path`, in the format its extension asks for.
+pub fn write(
+    path: &Path,
+    equations: &HashMap<&str, Equation<'_>>,
+    suspects: &[Suspect<'_>],
+) -> eyre::Result<()> {
+    let graph = match Format::from_path(path) {
+        Format::Dot => dot(equations, suspects),
+        Format::Mermaid => mermaid(equations, suspects),
+    };
+
+    fs::write(path, graph)?;
+    Ok(())
+}
+
+/// Draws the circuit of `input` to `day24.dot` and `day24.mmd` in `dir`.
+pub fn inspect(input: &str, dir: &Path) -> eyre::Result<()> {
+    let (_, equations) = super::parse::parse(input)?;
+    let suspects = super::part2::adder::suspects(&equations);
+
+    for file in ["day24.dot", "day24.mmd"] {
+        write(&dir.join(file), &equations, &suspects)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn export() {
+    let (_, equations) =
+        super::parse::parse("x00: 1\ny00: 0\n\nx00 XOR y00 -> z00\nx00 AND y00 -> z01").unwrap();
+    let suspects = [Suspect {
+        wire: "z01",
+        reason: "output bits come from XOR",
+    }];
+
+    let dot = dot(&equations, &suspects);
+    assert!(
+        dot.contains("subgraph cluster_00 { label=\"bit 00\"; x00; y00; z00 }"),
+        "{dot}"
+    );
+    assert!(
+        dot.contains("subgraph cluster_01 { label=\"bit 01\"; z01 }"),
+        "{dot}"
+    );
+    assert!(
+        dot.contains("z00 [label=\"z00\\nXOR\", fillcolor=\"#b2df8a\"];"),
+        "{dot}"
+    );
+    assert!(dot.contains("color=red"), "{dot}");
+    assert!(dot.contains("x00 -> z01;"), "{dot}");
+
+    let mermaid = mermaid(&equations, &suspects);
+    assert!(
+        mermaid.contains(
+            "subgraph bit00 [bit 00]\n        w_x00[x00]\n        w_y00[y00]\n        w_z00[z00]\n"
+        ),
+        "{mermaid}"
+    );
+    assert!(mermaid.contains("w_z01[\"z01 AND\"]:::AND"), "{mermaid}");
+    assert!(mermaid.contains("w_y00 --> w_z00"), "{mermaid}");
+    assert!(mermaid.contains("class w_z01 suspect"), "{mermaid}");
+
+    assert_eq!(Format::from_path(Path::new("day24.mmd")), Format::Mermaid);
+    assert_eq!(Format::from_path(Path::new("day24.dot")), Format::Dot);
+}
@@ -1,5 +1,7 @@
+mod circuit;
+pub(super) mod graph;
 mod part1;
 mod part2;
-mod types;
-mod parse;
+pub(super) mod types;
+pub(super) mod parse;
 mod example;
@@ -2,18 +2,23 @@ use std::collections::HashMap;
 
 use winnow::{
     ascii::{newline, space1},
-    combinator::{alt, separated, trace},
+    combinator::{alt, cut_err, separated, trace},
     error::StrContext,
     seq,
     token::take_while,
     PResult, Parser as _,
 };
 
-use crate::solutions::day24::types::Gate;
+use crate::{
+    parse::{self, ParseError},
+    solutions::day24::types::Gate,
+};
 
 use super::types::Equation;
 
-pub fn parse(input: &str) -> (HashMap<&str, bool>, HashMap<&str, Equation<'_>>) {
+pub type Circuit<'a> = (HashMap<&'a str, bool>, HashMap<&'a str, Equation<'a>>);
+
+pub fn parse(input: &str) -> Result<Circuit<'_>, ParseError> {
     fn wire<'a>(input: &mut &'a str) -> PResult<&'a str> {
         let parser = take_while(3, (b'a'..=b'z', b'0'..=b'9'));
         trace("wire", parser).parse_next(input)
@@ -45,7 +50,7 @@ pub fn parse(input: &str) -> (HashMap<&str, bool>, HashMap<&str, Equation<'_>>)
         ));
 
         trace("gate", parser)
-            .context(StrContext::Label("Couldn't parse gate"))
+            .context(StrContext::Label("gate"))
             .parse_next(input)
     }
 
@@ -53,7 +58,7 @@ pub fn parse(input: &str) -> (HashMap<&str, bool>, HashMap<&str, Equation<'_>>)
         let parser = seq!((
             wire,
             _: space1,
-            gate,
+            cut_err(gate),
             _: space1,
             wire,
             _: space1,
@@ -69,14 +74,14 @@ pub fn parse(input: &str) -> (HashMap<&str, bool>, HashMap<&str, Equation<'_>>)
         separated(0.., equation, newline).parse_next(input)
     }
 
-    trace(
+    let parser = trace(
         "whole",
         seq!((
             initial_values,
-            _: "\n\n",
+            _: parse::blank_line,
             equations
         )),
-    )
-    .parse(input.trim_ascii())
-    .unwrap()
+    );
+
+    parse::all(input, parser)
 }
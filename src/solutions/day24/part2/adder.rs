@@ -0,0 +1,183 @@
+//! Checks the circuit against the shape of a ripple-carry adder. Bit `n` is a
+//! full adder:
+//!
+//! ```text
+//! sum    = xNN XOR yNN
+//! zNN    = sum XOR carry
+//! direct = xNN AND yNN
+//! chain  = sum AND carry
+//! carry' = direct OR chain
+//! ```
+//!
+//! Bit 0 is only `z00 = x00 XOR y00` and `carry = x00 AND y00`, and the last
+//! `z` is the last carry.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::solutions::day24::types::{Equation, Gate, Pair};
+
+/// A wire whose gate doesn't fit in the adder, and which rule it breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Suspect<'i> {
+    pub wire: &'i str,
+    pub reason: &'static str,
+}
+
+fn is_input(wire: &str) -> bool {
+    wire.starts_with('x') || wire.starts_with('y')
+}
+
+/// The wires that break the pattern, sorted.
+pub fn suspects<'i>(equations: &HashMap<&'i str, Equation<'i>>) -> Vec<Suspect<'i>> {
+    let mut consumers = HashMap::<&str, Vec<Gate>>::new();
+    for equation in equations.values() {
+        for operand in equation.operands() {
+            consumers.entry(operand).or_default().push(equation.gate);
+        }
+    }
+
+    let feeds = |wire: &str, gate: Gate| {
+        consumers
+            .get(wire)
+            .is_some_and(|gates| gates.contains(&gate))
+    };
+    let last_z = equations
+        .keys()
+        .copied()
+        .filter(|wire| wire.starts_with('z'))
+        .max();
+
+    let mut suspects = BTreeMap::new();
+    for (&wire, equation) in equations {
+        let from_inputs = equation.operands().all(is_input);
+        let first_bit = from_inputs && equation.operands().all(|operand| operand.ends_with("00"));
+
+        let reason = match equation.gate {
+            Gate::Or if wire.starts_with('z') && Some(wire) != last_z => {
+                Some("only the last output bit is a carry")
+            }
+            Gate::And if wire.starts_with('z') => Some("output bits come from XOR"),
+            Gate::Xor if Some(wire) == last_z => Some("the last output bit is a carry"),
+
+            Gate::Xor if !from_inputs && !wire.starts_with('z') => {
+                Some("a sum XOR carry is an output bit")
+            }
+            Gate::Xor if from_inputs && !first_bit && !feeds(wire, Gate::Xor) => {
+                Some("xNN XOR yNN goes into the output bit's XOR")
+            }
+            Gate::And if !first_bit && !feeds(wire, Gate::Or) => {
+                Some("an AND goes into the next carry's OR")
+            }
+            Gate::Or
+                if Some(wire) != last_z && !(feeds(wire, Gate::Xor) && feeds(wire, Gate::And)) =>
+            {
+                Some("a carry goes into the next bit's XOR and AND")
+            }
+            _ => None,
+        };
+
+        if let Some(reason) = reason {
+            suspects.insert(wire, reason);
+        }
+    }
+
+    suspects
+        .into_iter()
+        .map(|(wire, reason)| Suspect { wire, reason })
+        .collect()
+}
+
+/// Swaps the outputs of the two wires of `pair`.
+pub fn swap<'i>(equations: &mut HashMap<&'i str, Equation<'i>>, pair: Pair<'i>) {
+    let (Some(a), Some(b)) = (equations.remove(pair.a), equations.remove(pair.b)) else {
+        panic!("Both wires of {pair} should be outputs of a gate");
+    };
+
+    equations.insert(pair.a, b);
+    equations.insert(pair.b, a);
+}
+
+/// Pairs up the suspects so that swapping each pair clears both of its wires.
+/// Returns `None` if some suspect can't be fixed like that.
+pub fn swaps<'i>(equations: &HashMap<&'i str, Equation<'i>>) -> Option<Vec<Pair<'i>>> {
+    let mut equations = equations.clone();
+    let mut swaps = Vec::new();
+
+    loop {
+        let current = suspects(&equations);
+        let Some(first) = current.first() else {
+            return Some(swaps);
+        };
+
+        let fix = current[1..]
+            .iter()
+            .map(|other| Pair::new(first.wire, other.wire))
+            .find(|&pair| {
+                let mut swapped = equations.clone();
+                swap(&mut swapped, pair);
+
+                let remaining = suspects(&swapped);
+                remaining.len() + 2 <= current.len()
+                    && !remaining
+                        .iter()
+                        .any(|suspect| suspect.wire == pair.a || suspect.wire == pair.b)
+            })?;
+
+        swap(&mut equations, fix);
+        swaps.push(fix);
+    }
+}
+
+#[cfg(test)]
+fn adder(bits: usize) -> String {
+    let mut lines = Vec::new();
+    for bit in 0..bits {
+        lines.push(format!("x{bit:02}: 0"));
+        lines.push(format!("y{bit:02}: 0"));
+    }
+
+    lines.push(String::new());
+    lines.push("x00 XOR y00 -> z00".to_string());
+    lines.push("x00 AND y00 -> c00".to_string());
+    for bit in 1..bits {
+        let carry = format!("c{:02}", bit - 1);
+        let next_carry = if bit == bits - 1 {
+            format!("z{bits:02}")
+        } else {
+            format!("c{bit:02}")
+        };
+
+        lines.push(format!("x{bit:02} XOR y{bit:02} -> s{bit:02}"));
+        lines.push(format!("s{bit:02} XOR {carry} -> z{bit:02}"));
+        lines.push(format!("y{bit:02} AND x{bit:02} -> d{bit:02}"));
+        lines.push(format!("{carry} AND s{bit:02} -> a{bit:02}"));
+        lines.push(format!("d{bit:02} OR a{bit:02} -> {next_carry}"));
+    }
+
+    lines.join("\n")
+}
+
+#[test]
+fn finds_swapped_wires() {
+    use crate::solutions::day24::{circuit, parse::parse};
+
+    let input = adder(6);
+    let (mut inputs, mut equations) = parse(&input).unwrap();
+    assert_eq!(suspects(&equations), []);
+
+    // Add the numbers to make sure the test circuit is right.
+    for (wire, value) in inputs.iter_mut() {
+        *value = matches!(*wire, "x00" | "x03" | "x05" | "y01" | "y03" | "y05");
+    }
+    let values = circuit::evaluate(&inputs, &equations).unwrap();
+    assert_eq!(circuit::read_number(&values, 'z').unwrap(), 0b101001 + 0b101010);
+
+    swap(&mut equations, Pair::new("z02", "d04"));
+    swap(&mut equations, Pair::new("s03", "d03"));
+    let suspects = suspects(&equations).iter().map(|suspect| suspect.wire).collect::<Vec<_>>();
+    assert_eq!(suspects, ["d03", "d04", "s03", "z02"]);
+
+    let mut swaps = swaps(&equations).unwrap();
+    swaps.sort();
+    assert_eq!(swaps, [Pair::new("d03", "s03"), Pair::new("d04", "z02")]);
+}
@@ -0,0 +1,182 @@
+//! 2D positions and directions, shared by the grid days.
+//!
+//! Like in [`crate::grid`], `x` goes right and `y` goes down, so `Up` is
+//! `(0, -1)`.
+
+use std::{fmt, ops::{Add, AddAssign, Sub}};
+
+use crate::grid::Position;
+
+pub type Vec2 = nalgebra::Vector2<i64>;
+
+pub trait Vec2Ext: Sized {
+    fn manhattan(&self, other: &Self) -> i64;
+
+    /// From a position in a [`crate::grid::Grid`], `(x, y)`.
+    fn from_position(position: Position) -> Self;
+
+    /// The position in a [`crate::grid::Grid`], if it isn't negative.
+    fn to_position(&self) -> Option<Position>;
+
+    /// The four orthogonal neighbours.
+    fn neighbors4(self) -> impl Iterator<Item = Self>;
+}
+
+impl Vec2Ext for Vec2 {
+    fn manhattan(&self, other: &Self) -> i64 {
+        (self - other).abs().sum()
+    }
+
+    fn from_position((x, y): Position) -> Self {
+        Vec2::new(x as i64, y as i64)
+    }
+
+    fn to_position(&self) -> Option<Position> {
+        Some((usize::try_from(self.x).ok()?, usize::try_from(self.y).ok()?))
+    }
+
+    fn neighbors4(self) -> impl Iterator<Item = Self> {
+        Direction::ALL.into_iter().map(move |direction| self + direction)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise, starting from `Up`.
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn vec(self) -> Vec2 {
+        match self {
+            Self::Up => Vec2::new(0, -1),
+            Self::Right => Vec2::new(1, 0),
+            Self::Down => Vec2::new(0, 1),
+            Self::Left => Vec2::new(-1, 0),
+        }
+    }
+
+    pub fn from_vec(vec: Vec2) -> Option<Self> {
+        Self::ALL.into_iter().find(|direction| direction.vec() == vec)
+    }
+
+    /// Clockwise.
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    /// Counter-clockwise.
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+
+    /// From an arrow: `^`, `>`, `v` or `<`.
+    pub fn from_char(char: char) -> Option<Self> {
+        match char {
+            '^' => Some(Self::Up),
+            '>' => Some(Self::Right),
+            'v' => Some(Self::Down),
+            '<' => Some(Self::Left),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+        }
+    }
+
+    /// As `(dx, dy)`, for [`crate::grid::Grid::offset`].
+    pub fn delta(self) -> (isize, isize) {
+        let vec = self.vec();
+        (vec.x as isize, vec.y as isize)
+    }
+
+    /// From one of `pathfinding`'s `DIRECTIONS_4`, which are `(row, column)` deltas.
+    pub fn from_pathfinding((row, column): (isize, isize)) -> Option<Self> {
+        Self::from_vec(Vec2::new(column as i64, row as i64))
+    }
+
+    /// As one of `pathfinding`'s `DIRECTIONS_4`, which are `(row, column)` deltas.
+    pub fn to_pathfinding(self) -> (isize, isize) {
+        let (dx, dy) = self.delta();
+        (dy, dx)
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = char;
+    fn try_from(char: char) -> Result<Self, Self::Error> {
+        Self::from_char(char).ok_or(char)
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl Add<Direction> for Vec2 {
+    type Output = Vec2;
+    fn add(self, direction: Direction) -> Self::Output {
+        self + direction.vec()
+    }
+}
+
+impl AddAssign<Direction> for Vec2 {
+    fn add_assign(&mut self, direction: Direction) {
+        *self += direction.vec();
+    }
+}
+
+impl Sub<Direction> for Vec2 {
+    type Output = Vec2;
+    fn sub(self, direction: Direction) -> Self::Output {
+        self - direction.vec()
+    }
+}
+
+#[test]
+fn directions() {
+    use pathfinding::matrix::directions::DIRECTIONS_4;
+
+    assert_eq!(Direction::Up.turn_right(), Direction::Right);
+    assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    assert_eq!(Direction::Left.opposite(), Direction::Right);
+
+    for direction in Direction::ALL {
+        assert_eq!(Direction::from_char(direction.to_char()), Some(direction));
+        assert_eq!(Direction::from_vec(direction.vec()), Some(direction));
+        assert_eq!(Direction::from_pathfinding(direction.to_pathfinding()), Some(direction));
+        assert_eq!(direction.turn_left().turn_right(), direction);
+    }
+
+    let mut from_pathfinding =
+        DIRECTIONS_4.map(|delta| Direction::from_pathfinding(delta).unwrap());
+    from_pathfinding.sort();
+    assert_eq!(from_pathfinding, Direction::ALL);
+
+    let position = Vec2::new(3, -2);
+    assert_eq!(position + Direction::Down, Vec2::new(3, -1));
+    assert_eq!(position.manhattan(&Vec2::zeros()), 5);
+    assert_eq!(position.to_position(), None);
+    assert_eq!(Vec2::from_position((4, 1)).to_position(), Some((4, 1)));
+}